@@ -1,20 +1,96 @@
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
+
+use clap_complete::engine::{ArgValueCandidates, ArgValueCompleter};
+
+use crate::{
+    completions,
+    day4::automaton::{Edges, Neighbourhood, parse_counts},
+    inputs::parse_profile,
+    params::parse_assignment,
+};
+
 #[derive(clap::Parser)]
-#[command(version, about, long_about = None)]
+#[command(name = "aoc", version, about, long_about = None)]
 pub struct Cli {
     #[clap(subcommand)]
     pub command: Command,
+
+    /// When to colour the output
+    #[arg(long, global = true, value_enum, default_value_t, env = "AOC_COLOR")]
+    pub color: ColorMode,
 }
 
 #[derive(Debug, clap::Subcommand)]
 pub enum Command {
     /// Run the code for a given day and part
     Run(RunConfig),
+
+    /// Compare the optimised and reference code on generated inputs of increasing size
+    Bench(BenchConfig),
+
+    /// Generate a random input for a day, reproducible from the seed
+    Gen(GenConfig),
+
+    /// Check the optimised code against the reference code on many generated inputs
+    DiffTest(DiffTestConfig),
+
+    /// Shrink an input that panics or disagrees with the reference to a minimal one that still does
+    Shrink(ShrinkConfig),
+
+    /// Mutate a day's inputs to find ones that make parsing or solving panic
+    Fuzz(FuzzConfig),
+
+    /// Check a day's solution against the recorded answers of every stored input profile
+    Validate(ValidateConfig),
+
+    /// Check which assumptions the solutions make about a day's input hold
+    CheckInput(CheckInputConfig),
+
+    /// Print a day's input as parsed, to check what the solutions are given
+    Parse(ParseConfig),
+
+    /// Print statistics about a day's input, such as its size and the range of its values
+    Inspect(InspectConfig),
+
+    /// Run a configurable cellular automaton over a day's grid
+    Simulate(SimulateConfig),
+
+    /// Look up IDs given as arguments, or read from stdin, against a day's input
+    Query(QueryConfig),
+
+    /// Print statistics about how a day's input behaves beyond the puzzle answers
+    Analyze(AnalyzeConfig),
+
+    /// List the implemented days
+    List(ListConfig),
+
+    /// Inspect the settings read from `aoc.toml`
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+
+    /// Print the script that sets up completions for a shell
+    Completions(CompletionsConfig),
+
+    /// Print the man page
+    Man(ManConfig),
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigCommand {
+    /// Print the value of every option and whether it comes from the environment, a file or the
+    /// default
+    Show,
 }
 
 #[derive(Debug, clap::Args, Clone)]
 pub struct RunConfig {
     /// Which day to run
-    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=12))]
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u8).range(1..=12),
+        add = ArgValueCandidates::new(completions::days),
+    )]
     pub day: u8,
 
     /// The part for the selected day to run
@@ -26,8 +102,226 @@ pub struct RunConfig {
     pub verbose: bool,
 
     /// Enable measuring the time it took to complete the problem
-    #[arg(short, long, default_value_t)]
+    #[arg(short, long, default_value_t, env = "AOC_METRICS")]
     pub metrics: bool,
+
+    /// Read the input from this file rather than the day's own input
+    #[arg(short, long, value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// The directory holding each profile's inputs, `$XDG_DATA_HOME/aoc/inputs` when not given
+    #[arg(long, value_name = "DIR", env = "AOC_INPUT_ROOT")]
+    pub input_root: Option<PathBuf>,
+
+    /// Whose inputs to read, from `<input-root>/<profile>/dayN.txt`
+    #[arg(long, value_name = "NAME", env = "AOC_PROFILE", value_parser = parse_profile)]
+    pub profile: Option<String>,
+
+    /// Run the reference implementation instead of the optimised one
+    #[arg(short, long, default_value_t)]
+    pub reference: bool,
+
+    /// Set a parameter of the selected day, see `list --params`
+    #[arg(
+        long = "param",
+        value_name = "NAME=VALUE",
+        value_parser = parse_assignment,
+        add = ArgValueCompleter::new(completions::params),
+    )]
+    pub params: Vec<(String, String)>,
+
+    /// Cancel solving after this many seconds
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds, env = "AOC_TIMEOUT")]
+    pub timeout: Option<Duration>,
+
+    /// Write the timed spans to this file in Chrome's trace event format, for Perfetto or
+    /// chrome://tracing
+    #[arg(long, value_name = "FILE")]
+    pub trace_timeline: Option<PathBuf>,
+
+    /// How to print the answers
+    #[arg(long, value_enum, default_value_t, env = "AOC_FORMAT")]
+    pub format: Format,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct BenchConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// The sizes of the inputs to generate, as a comma separated list
+    #[arg(short, long, value_delimiter = ',', default_values_t = [100, 1000])]
+    pub size: Vec<usize>,
+
+    /// The seed for the random input generator
+    #[arg(long, default_value_t = 2025)]
+    pub seed: u64,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct GenConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// How large an input to generate, in lines, ranges or grid rows depending on the day
+    #[arg(short, long, default_value_t = 100)]
+    pub size: usize,
+
+    /// The seed for the random input generator
+    #[arg(long, default_value_t = 2025)]
+    pub seed: u64,
+
+    /// Write the input to this file rather than stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct DiffTestConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// How many inputs to generate and compare
+    #[arg(short, long, default_value_t = 1000)]
+    pub cases: usize,
+
+    /// The largest input to generate, with each case picking a size up to it
+    #[arg(short, long, default_value_t = 20)]
+    pub size: usize,
+
+    /// The seed the seed of each case is drawn from
+    #[arg(long, default_value_t = 2025)]
+    pub seed: u64,
+
+    /// Shrink the first case that disagrees and write it to this file
+    #[arg(long, value_name = "FILE")]
+    pub shrink_to: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ShrinkConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// Where to write the shrunk input
+    #[arg(short, long, value_name = "FILE", default_value = "shrunk.txt")]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct FuzzConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// How many mutated inputs to try
+    #[arg(short = 'n', long, default_value_t = 10000)]
+    pub iterations: usize,
+
+    /// The seed for the mutations and the generated input they start from
+    #[arg(long, default_value_t = 2025)]
+    pub seed: u64,
+
+    /// The directory to save inputs that panic into
+    #[arg(short, long, value_name = "DIR", default_value = "fuzz")]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ValidateConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct CheckInputConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ParseConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct InspectConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct SimulateConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// Which surrounding cells count as neighbours
+    #[arg(long, value_enum, default_value_t)]
+    pub neighbourhood: Neighbourhood,
+
+    /// How far away a cell can be and still count as a neighbour
+    #[arg(long, default_value_t = 1)]
+    pub radius: usize,
+
+    /// Whether the grid wraps around at its edges
+    #[arg(long, value_enum, default_value_t)]
+    pub edges: Edges,
+
+    /// Neighbour counts that keep an occupied cell occupied, such as `4-` or `2-3`
+    #[arg(long, default_value = "4-", value_parser = parse_counts)]
+    pub survive: RangeInclusive<usize>,
+
+    /// Neighbour counts that fill an empty cell, never when omitted
+    #[arg(long, value_parser = parse_counts)]
+    pub birth: Option<RangeInclusive<usize>>,
+
+    /// Stop after this many generations rather than running until stable
+    #[arg(short, long)]
+    pub generations: Option<usize>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct QueryConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// The IDs to look up, read one per line from stdin when omitted
+    pub ids: Vec<u64>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct AnalyzeConfig {
+    #[command(flatten)]
+    pub run: RunConfig,
+
+    /// Find the first rotation passing the target more than this many times
+    #[arg(long, default_value_t = 1)]
+    pub passes: u64,
+
+    /// How many consecutive positions to group into each histogram row
+    #[arg(long, default_value_t = 1)]
+    pub bucket: usize,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ListConfig {
+    /// Describe the parameters each day accepts
+    #[arg(long, default_value_t)]
+    pub params: bool,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct CompletionsConfig {
+    /// The shell to complete for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ManConfig {
+    /// Write a page for each command into this directory rather than printing the main page
+    #[arg(short, long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum, Default, PartialEq, Eq)]
@@ -41,3 +335,39 @@ pub enum Part {
     #[default]
     Both,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Coloured output for reading in a terminal
+    #[default]
+    Text,
+
+    /// A single JSON object, for scripts
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Colour the output unless `NO_COLOR` is set or it isn't going to a terminal
+    #[default]
+    Auto,
+
+    Always,
+
+    Never,
+}
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let seconds = value
+        .parse::<f64>()
+        .map_err(|_| format!("'{value}' is not a number of seconds"))?;
+
+    Duration::try_from_secs_f64(seconds).map_err(|error| error.to_string())
+}
@@ -0,0 +1,71 @@
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+};
+
+use anyhow::{Context, anyhow};
+use clap::CommandFactory;
+use clap_complete::{
+    engine::CompletionCandidate,
+    env::{CompleteEnv, Shells},
+};
+
+use crate::{
+    DAYS,
+    cli::{Cli, Shell},
+    error::Error,
+};
+
+/// The environment variable the shell sets when calling back into `aoc` for completions.
+const VAR: &str = "COMPLETE";
+
+/// Answers the shell when it's calling back for completions, exiting once done, and otherwise
+/// returns so the command line is parsed as usual.
+pub fn complete_if_asked() {
+    CompleteEnv::with_factory(Cli::command).var(VAR).complete();
+}
+
+/// Prints the script that registers `aoc`'s completions with `shell`.
+pub fn print_script(shell: Shell) -> Result<(), Error> {
+    let name = match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+    };
+
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(name)
+        .ok_or_else(|| anyhow!("Completions for {name} aren't supported"))?;
+
+    let bin = env!("CARGO_BIN_NAME");
+    let mut out = io::stdout().lock();
+    completer
+        .write_registration(VAR, bin, bin, bin, &mut out)
+        .and_then(|_| out.flush())
+        .context("Failed to write the completion script")?;
+
+    Ok(())
+}
+
+/// The implemented days, for `--day`.
+pub fn days() -> Vec<CompletionCandidate> {
+    DAYS.iter()
+        .map(|(day, _)| CompletionCandidate::new(day.to_string()))
+        .collect()
+}
+
+/// The parameters of every day starting with what's typed so far, completed up to the `=` for
+/// `--param`. Which day is being run isn't known while completing, so the help names it.
+pub fn params(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+
+    DAYS.iter()
+        .flat_map(|(day, params)| params.iter().map(move |param| (day, param)))
+        .filter(|(_, param)| param.name.starts_with(current.as_ref()))
+        .map(|(day, param)| {
+            let help = format!("Day {day}: {}", param.help);
+            CompletionCandidate::new(format!("{}=", param.name)).help(Some(help.into()))
+        })
+        .collect()
+}
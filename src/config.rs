@@ -0,0 +1,395 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{Arg, Command, CommandFactory, FromArgMatches};
+use colored::Colorize;
+use toml::{Table, Value};
+
+use crate::{cli::Cli, error::Error};
+
+const FILE_NAME: &str = "aoc.toml";
+
+/// Defaults for the command line options, read from the project's `aoc.toml` and then the user's,
+/// which overrides it.
+///
+/// Top level keys set an option for every command that has it, while a table named after a
+/// command sets options for that command alone. The values become the options' defaults, so
+/// anything given on the command line or through the environment still wins.
+pub struct Settings {
+    /// The files found, the project's before the user's.
+    files: Vec<(PathBuf, Table)>,
+}
+
+impl Settings {
+    pub fn load() -> Result<Self, Error> {
+        let mut paths = vec![];
+        for path in [project_file(), user_file()].into_iter().flatten() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        let files = paths
+            .into_iter()
+            .filter_map(|path| read(path).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { files })
+    }
+
+    /// Parses the command line, with the settings filling in whatever it leaves out.
+    pub fn parse(&self) -> Result<Cli, Error> {
+        let matches = self.apply(Cli::command())?.get_matches();
+
+        Ok(Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit()))
+    }
+
+    /// Makes the settings the defaults of the options they name, after checking each does.
+    fn apply(&self, mut command: Command) -> Result<Command, Error> {
+        self.validate(&command)?;
+
+        for (id, long) in options(&command) {
+            if let Some((value, _)) = self.lookup(None, &long) {
+                command = command.mut_arg(id, |arg| default(arg, value));
+            }
+        }
+
+        let names = command
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect::<Vec<_>>();
+
+        for name in names {
+            command = command.mut_subcommand(&name, |mut subcommand| {
+                for (id, long) in options(&subcommand) {
+                    if let Some((value, _)) = self.lookup(Some(&name), &long) {
+                        subcommand = subcommand.mut_arg(id, |arg| default(arg, value));
+                    }
+                }
+
+                subcommand
+            });
+        }
+
+        Ok(command)
+    }
+
+    /// Checks every key names an option, and every table a command, so typos aren't ignored.
+    fn validate(&self, command: &Command) -> Result<(), Error> {
+        let invalid = |path: &Path, message: String| Error::Config(path.to_path_buf(), message);
+
+        for (path, table) in &self.files {
+            for (key, value) in table {
+                let Some(section) = value.as_table() else {
+                    let known = has_option(command, key)
+                        || command
+                            .get_subcommands()
+                            .any(|subcommand| has_option(subcommand, key));
+                    if !known {
+                        return Err(invalid(path, format!("There's no option named '{key}'")));
+                    }
+
+                    check_value(path, key, value)?;
+                    continue;
+                };
+
+                let Some(subcommand) = command.find_subcommand(key) else {
+                    return Err(invalid(path, format!("There's no command named '{key}'")));
+                };
+
+                for (option, value) in section {
+                    if !has_option(subcommand, option) {
+                        let message = format!("The '{key}' command has no option named '{option}'");
+                        return Err(invalid(path, message));
+                    }
+
+                    check_value(path, &format!("{key}.{option}"), value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The value set for `key` in `command`'s table, or else at the top level, along with the
+    /// file it's set in. The user's file is searched before the project's.
+    fn lookup(&self, command: Option<&str>, key: &str) -> Option<(&Value, &Path)> {
+        self.files.iter().rev().find_map(|(path, table)| {
+            let scoped = command
+                .and_then(|command| table.get(command))
+                .and_then(Value::as_table)
+                .and_then(|section| section.get(key));
+            let value = scoped.or_else(|| table.get(key).filter(|value| !value.is_table()))?;
+
+            Some((value, path.as_path()))
+        })
+    }
+
+    /// Where the value of `arg` comes from when it isn't given on the command line.
+    fn source(&self, command: Option<&str>, arg: &Arg) -> (String, Source) {
+        let variable = arg
+            .get_env()
+            .and_then(|name| Some((name.to_str()?, env::var(name).ok()?)));
+        if let Some((name, value)) = variable {
+            return (value, Source::Env(name.to_string()));
+        }
+
+        if let Some((value, path)) = self.lookup(command, arg.get_long().unwrap_or_default()) {
+            return (values(value).join(","), Source::File(path.to_path_buf()));
+        }
+
+        let defaults = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect::<Vec<_>>();
+
+        match defaults.is_empty() {
+            true => (String::new(), Source::Unset),
+            false => (defaults.join(","), Source::Default),
+        }
+    }
+
+    /// Prints the files read and the value of each option that isn't given on the command line,
+    /// with where it comes from.
+    pub fn show(&self) {
+        println!("🎄 {}", "Configuration".bold());
+
+        match self.files.is_empty() {
+            true => println!("   {}", format!("No {FILE_NAME} found").dimmed()),
+            false => {
+                for (path, _) in &self.files {
+                    println!("   Read {}", path.display());
+                }
+            }
+        }
+
+        let command = Cli::command();
+        let run = command
+            .find_subcommand("run")
+            .map(options)
+            .unwrap_or_default();
+
+        self.show_options(None, "Every command", command.get_arguments());
+
+        // The commands built on `run` share its options, so only their own are listed.
+        for subcommand in command.get_subcommands() {
+            let name = subcommand.get_name();
+            let own = subcommand.get_arguments().filter(|arg| {
+                name == "run" || !run.iter().any(|(_, long)| arg.get_long() == Some(long))
+            });
+
+            self.show_options(Some(name), name, own);
+        }
+    }
+
+    fn show_options<'a>(
+        &self,
+        command: Option<&str>,
+        title: &str,
+        args: impl Iterator<Item = &'a Arg>,
+    ) {
+        let rows = args
+            .filter(|arg| !matches!(arg.get_id().as_str(), "help" | "version"))
+            .filter_map(|arg| {
+                let long = arg.get_long()?;
+                let (value, source) = self.source(command, arg);
+                Some((long, value, source))
+            })
+            .collect::<Vec<_>>();
+
+        if rows.is_empty() {
+            return;
+        }
+
+        let width = rows
+            .iter()
+            .map(|(long, ..)| long.len())
+            .max()
+            .unwrap_or_default();
+        let values = rows
+            .iter()
+            .map(|(_, value, _)| value.len())
+            .max()
+            .unwrap_or_default()
+            .max("unset".len());
+
+        println!();
+        println!("   {}", title.bold());
+        for (long, value, source) in rows {
+            let value = match source {
+                Source::Unset => format!("{:<values$}", "unset").dimmed(),
+                _ => format!("{value:<values$}").green(),
+            };
+            let source = source.to_string().dimmed();
+            println!("      {long:<width$}  {value}  {source}");
+        }
+    }
+}
+
+/// Where the value of an option comes from.
+enum Source {
+    Env(String),
+    File(PathBuf),
+    Default,
+    Unset,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Default => write!(f, "default"),
+            Self::Unset => Ok(()),
+        }
+    }
+}
+
+/// The nearest `aoc.toml` in the working directory or above it.
+fn project_file() -> Option<PathBuf> {
+    let directory = env::current_dir().ok()?;
+
+    directory
+        .ancestors()
+        .map(|directory| directory.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// `$XDG_CONFIG_HOME/aoc/aoc.toml`.
+fn user_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("aoc").join(FILE_NAME))
+}
+
+fn read(path: PathBuf) -> Result<Option<(PathBuf, Table)>, Error> {
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(Error::Config(path, error.to_string())),
+    };
+
+    match toml::from_str(&content) {
+        Ok(table) => Ok(Some((path, table))),
+        Err(error) => Err(Error::Config(path, error.message().to_string())),
+    }
+}
+
+/// The options of `command` that have a long name, which is what the settings are keyed by.
+fn options(command: &Command) -> Vec<(clap::Id, String)> {
+    command
+        .get_arguments()
+        .filter_map(|arg| Some((arg.get_id().clone(), arg.get_long()?.to_string())))
+        .collect()
+}
+
+fn has_option(command: &Command, key: &str) -> bool {
+    options(command).iter().any(|(_, long)| long == key)
+}
+
+/// Only scalars and lists of scalars can stand in for what's typed on the command line.
+fn check_value(path: &Path, key: &str, value: &Value) -> Result<(), Error> {
+    let scalar = |value: &Value| !matches!(value, Value::Table(_) | Value::Array(_));
+    let valid = match value {
+        Value::Array(items) => items.iter().all(scalar),
+        value => scalar(value),
+    };
+
+    match valid {
+        true => Ok(()),
+        false => {
+            let message = format!("'{key}' must be a value or a list of values");
+            Err(Error::Config(path.to_path_buf(), message))
+        }
+    }
+}
+
+/// Makes `value` the default of `arg`, which then no longer has to be given.
+fn default(arg: Arg, value: &Value) -> Arg {
+    arg.default_values(values(value)).required(false)
+}
+
+/// The value as it would be typed on the command line, one string per item of a list.
+fn values(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Array(items) => items.iter().flat_map(values).collect(),
+        value => vec![value.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{CheckInputConfig, Command, Part, RunConfig};
+
+    /// Parses `args` with each of `files` read in turn, the project's first and the user's last.
+    fn parse(files: &[&str], args: &[&str]) -> Result<Cli, Error> {
+        let files = files
+            .iter()
+            .enumerate()
+            .map(|(index, content)| {
+                let path = PathBuf::from(format!("{index}/{FILE_NAME}"));
+                (path, toml::from_str(content).unwrap())
+            })
+            .collect();
+
+        let command = Settings { files }.apply(Cli::command())?;
+        let args = ["aoc"].iter().chain(args);
+
+        Ok(Cli::from_arg_matches(&command.try_get_matches_from(args).unwrap()).unwrap())
+    }
+
+    fn run(files: &[&str], args: &[&str]) -> RunConfig {
+        match parse(files, args).unwrap().command {
+            Command::Run(config) | Command::CheckInput(CheckInputConfig { run: config }) => config,
+            command => panic!("Expected a run, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn fills_in_options_left_out() {
+        let config = run(&["day = 3\npart = \"1\""], &["run"]);
+
+        assert_eq!(config.day, 3);
+        assert_eq!(config.part, Part::One);
+    }
+
+    #[test]
+    fn prefers_the_users_file_to_the_projects() {
+        let config = run(&["part = \"1\"", "part = \"2\""], &["run", "--day", "1"]);
+
+        assert_eq!(config.part, Part::Two);
+    }
+
+    #[test]
+    fn prefers_a_commands_table_to_the_top_level() {
+        let files = ["part = \"1\"\n[run]\npart = \"2\""];
+
+        assert_eq!(run(&files, &["run", "--day", "1"]).part, Part::Two);
+        assert_eq!(run(&files, &["check-input", "--day", "1"]).part, Part::One);
+    }
+
+    #[test]
+    fn prefers_the_command_line_to_the_files() {
+        let config = run(&["part = \"2\""], &["run", "--day", "1", "--part", "1"]);
+
+        assert_eq!(config.part, Part::One);
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_commands() {
+        for file in ["colour = \"never\"", "[run]\nsize = 3", "[walk]\nday = 1"] {
+            let result = parse(&[file], &["run", "--day", "1"]);
+            assert!(matches!(result, Err(Error::Config(..))), "{file}");
+        }
+    }
+
+    #[test]
+    fn rejects_tables_as_values() {
+        let result = parse(&["[run]\npart = { value = \"1\" }"], &["run", "--day", "1"]);
+
+        assert!(matches!(result, Err(Error::Config(..))));
+    }
+}
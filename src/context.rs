@@ -0,0 +1,95 @@
+use std::{
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{error::Error, metrics::Metrics, params::Params, progress::Progress};
+
+/// Everything a problem gets from the runner while it's being solved.
+pub struct SolveContext {
+    tracer: Tracer,
+    params: Params,
+    cancelled: Arc<AtomicBool>,
+    progress: Progress,
+    metrics: Arc<Metrics>,
+}
+
+impl SolveContext {
+    pub fn new(params: Params, verbose: bool, metrics: Arc<Metrics>) -> Self {
+        Self {
+            tracer: Tracer { enabled: verbose },
+            params,
+            cancelled: Arc::default(),
+            progress: Progress::default(),
+            metrics,
+        }
+    }
+
+    /// Cancels the solve once `timeout` has passed, for parts that check `cancelled`.
+    pub fn cancel_after(&self, timeout: Duration) {
+        let cancelled = Arc::clone(&self.cancelled);
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            cancelled.store(true, Ordering::Relaxed);
+        });
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.tracer.enabled
+    }
+
+    /// Prints the message built by `message` when verbose output is enabled.
+    pub fn trace<TFn, M>(&self, message: TFn)
+    where
+        TFn: FnOnce() -> M,
+        M: Display,
+    {
+        self.tracer.trace(message);
+    }
+
+    /// Fails with `Error::Cancelled` once the solve has been cancelled, to be checked in loops.
+    pub fn cancelled(&self) -> Result<(), Error> {
+        match self.cancelled.load(Ordering::Relaxed) {
+            true => Err(Error::Cancelled),
+            false => Ok(()),
+        }
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Times `f` under `name`, nested within the span the runner or the problem is already in.
+    /// Repeated spans add up, so a span can wrap each iteration of a loop.
+    pub fn span<TFn, R>(&self, name: &'static str, f: TFn) -> R
+    where
+        TFn: FnOnce() -> R,
+    {
+        self.metrics.span(name, f)
+    }
+}
+
+struct Tracer {
+    enabled: bool,
+}
+
+impl Tracer {
+    fn trace<TFn, M>(&self, message: TFn)
+    where
+        TFn: FnOnce() -> M,
+        M: Display,
+    {
+        if self.enabled {
+            println!("{}", message());
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::anyhow;
+use colored::Colorize;
+
+use crate::{cli::AnalyzeConfig, error::Error};
+
+use super::{Day1, Dial, Direction, Rotation};
+
+const PADDING: &str = "   ";
+
+/// The widest dial the per-position histogram is built for.
+const MAX_SIZE: u64 = 1_000_000;
+
+/// The width of the longest histogram bar.
+const BAR_WIDTH: u64 = 40;
+
+/// Explores how the dial behaves for the given rotations, beyond the two puzzle answers.
+pub fn analyze(config: &AnalyzeConfig, day: &Day1, input: &[Rotation]) -> Result<(), Error> {
+    // Every statistic below assumes a dial the start and target fit on.
+    Dial::new(day.size, day.start, day.target)?;
+
+    if day.size > MAX_SIZE {
+        return Err(anyhow!("Dials over {MAX_SIZE} positions are too large to analyze").into());
+    }
+
+    println!("🎄 {}", "Day 1 analysis".bold());
+
+    let (best, hits) = best_start(day, input);
+    let configured = landings_from(day, input, day.start);
+    println!(
+        "{PADDING}Best start:   {} lands on {} {} times, compared to {} from {}",
+        best.to_string().green(),
+        day.target,
+        hits.to_string().green(),
+        configured.to_string().yellow(),
+        day.start,
+    );
+
+    let label = format!("More than {} passes:", config.passes);
+    match first_exceeding(day, input, config.passes)? {
+        Some((index, rotation, passes)) => println!(
+            "{PADDING}{label} rotation {} ({rotation}) passes {} {passes} times",
+            (index + 1).to_string().green(),
+            day.target,
+        ),
+        None => println!("{PADDING}{label} {}", "never".yellow()),
+    }
+
+    println!();
+    report_histogram(config, day, input)
+}
+
+/// Finds the starting position which lands on the target the most, along with that count.
+///
+/// After `i` rotations the dial has moved the same net distance whatever the start, so a start
+/// lands on the target exactly when it cancels out that distance.
+fn best_start(day: &Day1, input: &[Rotation]) -> (u64, u64) {
+    let size = day.size as u128;
+    let mut counts = vec![0_u64; day.size as usize];
+    let mut distance = 0_u128;
+
+    for rotation in input {
+        let clicks = rotation.clicks as u128 % size;
+        distance = match rotation.direction {
+            Direction::Right => (distance + clicks) % size,
+            Direction::Left => (distance + size - clicks) % size,
+        };
+
+        counts[((day.target as u128 + size - distance) % size) as usize] += 1;
+    }
+
+    counts
+        .iter()
+        .enumerate()
+        .max_by(|(a_start, a), (b_start, b)| a.cmp(b).then(b_start.cmp(a_start)))
+        .map(|(start, count)| (start as u64, *count))
+        .unwrap_or_default()
+}
+
+fn landings_from(day: &Day1, input: &[Rotation], start: u64) -> u64 {
+    let size = day.size as u128;
+    let mut position = start as u128;
+
+    input
+        .iter()
+        .filter(|rotation| {
+            let clicks = rotation.clicks as u128 % size;
+            position = match rotation.direction {
+                Direction::Right => (position + clicks) % size,
+                Direction::Left => (position + size - clicks) % size,
+            };
+
+            position == day.target as u128
+        })
+        .count() as u64
+}
+
+/// Finds the first rotation which passes the target more than `passes` times on its own.
+fn first_exceeding(
+    day: &Day1,
+    input: &[Rotation],
+    passes: u64,
+) -> Result<Option<(usize, Rotation, u64)>, Error> {
+    let mut dial = Dial::new(day.size, day.start, day.target)?;
+
+    for (index, rotation) in input.iter().enumerate() {
+        let odometer = dial.odometer;
+        dial = dial.rotate(*rotation)?;
+
+        if dial.odometer - odometer > passes {
+            return Ok(Some((index, *rotation, dial.odometer - odometer)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Prints how often each position was landed on and passed, starting from the configured start.
+fn report_histogram(config: &AnalyzeConfig, day: &Day1, input: &[Rotation]) -> Result<(), Error> {
+    let size = day.size as usize;
+    let mut landed = vec![0_u64; size];
+
+    // Each rotation passes every position once per full turn, plus an arc for the remainder,
+    // which is added to a difference array so long rotations cost the same as short ones.
+    let mut arcs = vec![0_i64; size + 1];
+    let mut turns = 0_u64;
+    let mut position = day.start as usize;
+
+    for rotation in input {
+        turns += rotation.clicks / day.size;
+        let remainder = (rotation.clicks % day.size) as usize;
+
+        if remainder > 0 {
+            let (first, last) = match rotation.direction {
+                Direction::Right => (position + 1, position + remainder),
+                Direction::Left => (position + size - remainder, position + size - 1),
+            };
+
+            let (first, last) = (first % size, last % size);
+            arcs[first] += 1;
+            arcs[last + 1] -= 1;
+
+            if first > last {
+                arcs[0] += 1;
+                arcs[size] -= 1;
+            }
+
+            position = match rotation.direction {
+                Direction::Right => last,
+                Direction::Left => first,
+            };
+        }
+
+        landed[position] += 1;
+    }
+
+    let passed = arcs
+        .iter()
+        .take(size)
+        .scan(0_i64, |running, arc| {
+            *running += arc;
+            Some(*running as u64 + turns)
+        })
+        .collect::<Vec<_>>();
+
+    let bucket = config.bucket.max(1);
+    let rows = (0..size)
+        .step_by(bucket)
+        .map(|first| {
+            let last = (first + bucket).min(size) - 1;
+            let landed = landed[first..=last].iter().sum::<u64>();
+            let passed = passed[first..=last].iter().sum::<u64>();
+            (first, last, landed, passed)
+        })
+        .collect::<Vec<_>>();
+
+    let widest = rows
+        .iter()
+        .map(|(.., passed)| *passed)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    println!(
+        "{PADDING}{:<15} {:>10} {:>10}",
+        "Position".bold(),
+        "Landed".bold(),
+        "Passed".bold()
+    );
+
+    for (first, last, landed, passed) in rows {
+        let position = match first == last {
+            true => first.to_string(),
+            false => format!("{first}-{last}"),
+        };
+
+        let bar = "█".repeat((passed * BAR_WIDTH / widest) as usize);
+        println!(
+            "{PADDING}{position:<15} {landed:>10} {passed:>10}  {}",
+            bar.green()
+        );
+    }
+
+    Ok(())
+}
@@ -1,26 +1,79 @@
 use std::{fmt::Display, path::Path};
 
 use anyhow::{Context, anyhow};
+use serde::Serialize;
 
-use crate::{cli::RunConfig, error::Error, problem::Problem};
+use crate::{
+    context::SolveContext,
+    error::Error,
+    params::{ParamKind, ParamSpec},
+    problem::{Problem, extent},
+    rng::Rng,
+};
 
-pub struct Day1<'a> {
-    config: &'a RunConfig,
+pub mod analysis;
+mod reference;
+
+pub struct Day1 {
+    /// How many positions the dial has.
+    size: u64,
+
+    /// The position the dial starts at.
+    start: u64,
+
+    /// The position being counted.
+    target: u64,
 }
 
-impl<'a> Problem<'a> for Day1<'a> {
+impl Problem for Day1 {
     type Input = Vec<Rotation>;
-    type Answer1 = u16;
-    type Answer2 = u16;
+    type Answer1 = u64;
+    type Answer2 = u64;
+    type Shared = ();
 
-    fn init(config: &'a RunConfig) -> Self
+    const PARAMS: &'static [ParamSpec] = &[
+        ParamSpec {
+            name: "size",
+            kind: ParamKind::UInt,
+            default: "100",
+            help: "How many positions the dial has",
+            bounds: Some((1, u64::MAX)),
+        },
+        ParamSpec {
+            name: "start",
+            kind: ParamKind::UInt,
+            default: "50",
+            help: "The position the dial starts at",
+            bounds: Some((0, u64::MAX - 1)),
+        },
+        ParamSpec {
+            name: "target",
+            kind: ParamKind::UInt,
+            default: "0",
+            help: "The position to count landings on and passes of",
+            bounds: Some((0, u64::MAX - 1)),
+        },
+    ];
+
+    fn init(context: &SolveContext) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Self { config }
+        let params = context.params();
+
+        Ok(Self {
+            size: params.uint("size"),
+            start: params.uint("start"),
+            target: params.uint("target"),
+        })
     }
 
-    fn parse(&self, content: &str, _path: &Path) -> Result<Self::Input, Error> {
+    fn parse(
+        &self,
+        content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
         content
             .split('\n')
             .filter(|rotation| !rotation.trim().is_empty())
@@ -28,39 +81,102 @@ impl<'a> Problem<'a> for Day1<'a> {
             .collect()
     }
 
-    fn part1(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
+    fn part1(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        let dial = Dial::new(self.size, self.start, self.target)?;
         let (count, _) = input
             .iter()
-            .fold((0, Dial::new()), |(count, dial), rotation| {
-                let dial = dial.rotate(*rotation);
-                if self.config.verbose {
-                    println!("The dial is rotated {rotation} to point at {dial}");
-                }
+            .try_fold((0, dial), |(count, dial), rotation| {
+                context.cancelled()?;
+                let dial = dial.rotate(*rotation)?;
+                context.trace(|| format!("The dial is rotated {rotation} to point at {dial}"));
 
-                match dial.position {
-                    0 => (count + 1, dial),
-                    _ => (count, dial),
+                match dial.position == self.target {
+                    true => Ok::<_, Error>((count + 1, dial)),
+                    false => Ok((count, dial)),
                 }
-            });
+            })?;
 
         Ok(count)
     }
 
-    fn part2(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
-        let dial = input.iter().fold(Dial::new(), |dial, rotation| {
-            let dial = dial.rotate(*rotation);
-            if self.config.verbose {
-                println!("The dial is rotated {rotation} to point at {dial}");
-            }
+    fn part2(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        let dial = Dial::new(self.size, self.start, self.target)?;
+        let dial = input.iter().try_fold(dial, |dial, rotation| {
+            context.cancelled()?;
+            let dial = dial.rotate(*rotation)?;
+            context.trace(|| format!("The dial is rotated {rotation} to point at {dial}"));
 
-            dial
-        });
+            Ok::<_, Error>(dial)
+        })?;
 
         Ok(dial.odometer)
     }
+
+    fn reference_part1(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        let dial = (self.size, self.start, self.target);
+        Ok(reference::simulate(input, dial, context)?.landings)
+    }
+
+    fn reference_part2(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        let dial = (self.size, self.start, self.target);
+        Ok(reference::simulate(input, dial, context)?.passes)
+    }
+
+    fn inspect(&self, input: &Self::Input) -> Vec<(&'static str, String)> {
+        let left = input
+            .iter()
+            .filter(|rotation| rotation.direction == Direction::Left)
+            .count();
+        let clicks = input.iter().map(|rotation| rotation.clicks);
+        let total = clicks.clone().map(u128::from).sum::<u128>();
+
+        vec![
+            ("Rotations", input.len().to_string()),
+            ("Left / right", format!("{left} / {}", input.len() - left)),
+            ("Clicks", extent(clicks)),
+            ("Total clicks", total.to_string()),
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Result<String, Error> {
+        // Up to a few full turns, so passes through the target within a rotation are covered.
+        let max_clicks = self.size.saturating_mul(3).max(1);
+        let rotations = (0..size)
+            .map(|_| {
+                let direction = match rng.chance(0.5) {
+                    true => Direction::Left,
+                    false => Direction::Right,
+                };
+                let clicks = rng.range(1..=max_clicks);
+
+                Rotation { clicks, direction }.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(rotations)
+    }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
 pub enum Direction {
     Left,
     Right,
@@ -76,90 +192,109 @@ impl Display for Direction {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct Rotation {
-    value: i16,
+    clicks: u64,
     direction: Direction,
 }
 
 impl Rotation {
     pub fn parse(value: &str) -> Result<Self, Error> {
-        let (direction, value) = match value.split_at(1) {
-            ("L", value) => (Direction::Left, -Self::parse_value(value)?),
-            ("R", value) => (Direction::Right, Self::parse_value(value)?),
+        let (direction, clicks) = match value.split_at_checked(1) {
+            Some(("L", clicks)) => (Direction::Left, Self::parse_clicks(clicks)?),
+            Some(("R", clicks)) => (Direction::Right, Self::parse_clicks(clicks)?),
             value => {
                 return Err(anyhow!("Failed to parse rotation {value:?}").into());
             }
         };
 
-        Ok(Self { value, direction })
+        Ok(Self { clicks, direction })
     }
 
-    fn parse_value(value: &str) -> Result<i16, Error> {
+    fn parse_clicks(value: &str) -> Result<u64, Error> {
         let result = value
-            .parse::<i16>()
-            .with_context(|| format!("Non-i16 value given for rotation: '{value}'"))?;
+            .parse::<u64>()
+            .with_context(|| format!("Non-u64 value given for rotation: '{value}'"))?;
 
         Ok(result)
     }
-
-    pub fn normalize(&self) -> (u16, i16) {
-        let base = match self.direction {
-            Direction::Right => 100,
-            Direction::Left => -100,
-        };
-
-        ((self.value / base) as u16, self.value % base)
-    }
 }
 
 impl Display for Rotation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let direction = self.direction;
-        let value = self.value.abs();
-        write!(f, "{direction}{value}")
+        let clicks = self.clicks;
+        write!(f, "{direction}{clicks}")
     }
 }
 
 struct Dial {
+    /// How many positions the dial has, numbered from `0`.
+    pub size: u64,
+
+    /// The position the odometer counts passes of.
+    pub target: u64,
+
     /// The current position of the dial.
-    pub position: u16,
+    pub position: u64,
 
-    /// How many times the dial has passed `0`.
+    /// How many times the dial has passed `target`.
     ///
-    /// Be sure to get the dial serviced every 10,000 rotations!
-    pub odometer: u16,
+    /// Be sure to get the dial serviced every 18,446,744,073,709,551,615 rotations!
+    pub odometer: u64,
 }
 
 impl Dial {
-    pub fn new() -> Self {
-        Self {
-            position: 50,
-            odometer: 0,
+    pub fn new(size: u64, start: u64, target: u64) -> Result<Self, Error> {
+        if size == 0 {
+            return Err(anyhow!("A dial needs at least one position").into());
         }
+
+        if start >= size || target >= size {
+            return Err(anyhow!(
+                "Start {start} and target {target} must both be on a dial of size {size}"
+            )
+            .into());
+        }
+
+        Ok(Self {
+            size,
+            target,
+            position: start,
+            odometer: 0,
+        })
     }
 
-    pub fn rotate(self, rotation: Rotation) -> Self {
-        let (rotations, normalized) = rotation.normalize();
-        let position = self.position.wrapping_add_signed(normalized) % (u16::MAX - 99) % 100;
+    pub fn rotate(self, rotation: Rotation) -> Result<Self, Error> {
+        let size = self.size as u128;
+        let clicks = rotation.clicks as u128;
 
-        if position == 0 {
-            return Self {
-                position,
-                odometer: self.odometer + rotations + 1,
-            };
-        }
+        // Measuring from the target means passing it is the same as passing `0`.
+        let offset = (self.position as u128 + size - self.target as u128) % size;
 
-        let rotations = match (self.position, rotation.direction) {
-            (1.., Direction::Right) if position <= self.position => rotations + 1,
-            (1.., Direction::Left) if position >= self.position => rotations + 1,
-            _ => rotations,
+        let (offset, passes) = match rotation.direction {
+            Direction::Right => ((offset + clicks) % size, (offset + clicks) / size),
+            Direction::Left => {
+                let passes = match offset {
+                    0 => clicks / size,
+                    _ if clicks >= offset => (clicks - offset) / size + 1,
+                    _ => 0,
+                };
+
+                ((offset + size - clicks % size) % size, passes)
+            }
         };
 
-        Self {
-            position,
-            odometer: self.odometer + rotations,
-        }
+        let odometer = u64::try_from(passes)
+            .ok()
+            .and_then(|passes| self.odometer.checked_add(passes))
+            .with_context(|| format!("The odometer overflowed rotating {rotation} from {self}"))?;
+
+        Ok(Self {
+            position: ((offset + self.target as u128) % size) as u64,
+            odometer,
+            ..self
+        })
     }
 }
 
@@ -172,3 +307,93 @@ impl Display for Dial {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotate(dial: Dial, rotation: &str) -> Dial {
+        dial.rotate(Rotation::parse(rotation).unwrap()).unwrap()
+    }
+
+    /// Turns the dial a click at a time, counting each time it points at the target.
+    fn click(size: u64, start: u64, target: u64, rotation: &str) -> (u64, u64) {
+        let rotation = Rotation::parse(rotation).unwrap();
+        let (mut position, mut passes) = (start, 0);
+
+        for _ in 0..rotation.clicks {
+            position = match rotation.direction {
+                Direction::Left => (position + size - 1) % size,
+                Direction::Right => (position + 1) % size,
+            };
+            passes += (position == target) as u64;
+        }
+
+        (position, passes)
+    }
+
+    #[test]
+    fn follows_the_example() {
+        let rotations = [
+            "L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82",
+        ];
+        let positions = [82, 52, 0, 95, 55, 0, 99, 0, 14, 32];
+
+        let mut dial = Dial::new(100, 50, 0).unwrap();
+        for (rotation, position) in rotations.iter().zip(positions) {
+            dial = rotate(dial, rotation);
+            assert_eq!(dial.position, position, "after {rotation}");
+        }
+
+        assert_eq!(dial.odometer, 6);
+    }
+
+    #[test]
+    fn counts_every_pass_of_a_wide_rotation() {
+        let dial = rotate(Dial::new(100, 50, 0).unwrap(), "R1000");
+        assert_eq!((dial.position, dial.odometer), (50, 10));
+
+        let dial = rotate(Dial::new(100, 0, 0).unwrap(), "L1000");
+        assert_eq!((dial.position, dial.odometer), (0, 10));
+    }
+
+    #[test]
+    fn matches_turning_a_click_at_a_time() {
+        for size in [1, 2, 5, 12] {
+            for start in 0..size {
+                for target in 0..size {
+                    for clicks in 0..=size * 3 {
+                        for direction in ["L", "R"] {
+                            let rotation = format!("{direction}{clicks}");
+                            let dial = rotate(Dial::new(size, start, target).unwrap(), &rotation);
+
+                            assert_eq!(
+                                (dial.position, dial.odometer),
+                                click(size, start, target, &rotation),
+                                "{rotation} on a dial of {size} from {start} to {target}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn turns_dials_too_large_for_u64_arithmetic() {
+        let dial = Dial::new(u64::MAX, u64::MAX - 1, 0).unwrap();
+        let dial = rotate(dial, &format!("R{}", u64::MAX));
+
+        assert_eq!((dial.position, dial.odometer), (u64::MAX - 1, 1));
+    }
+
+    #[test]
+    fn fails_when_the_odometer_overflows() {
+        let dial = Dial {
+            odometer: u64::MAX,
+            ..Dial::new(1, 0, 0).unwrap()
+        };
+
+        assert!(dial.rotate(Rotation::parse("R1").unwrap()).is_err());
+    }
+}
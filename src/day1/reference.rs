@@ -0,0 +1,69 @@
+use anyhow::anyhow;
+
+use super::{Direction, Rotation};
+use crate::{context::SolveContext, error::Error};
+
+/// How many clicks turn between checks for cancellation and updates to the progress.
+const CHECK_EVERY: u64 = 1 << 16;
+
+/// The dial after turning through every rotation a click at a time.
+pub struct Simulation {
+    /// How many rotations left the dial pointing at the target.
+    pub landings: u64,
+
+    /// How many clicks left the dial pointing at the target.
+    pub passes: u64,
+}
+
+/// Turns the dial one click at a time, checking for the target after every click.
+pub fn simulate(
+    input: &[Rotation],
+    (size, start, target): (u64, u64, u64),
+    context: &SolveContext,
+) -> Result<Simulation, Error> {
+    if size == 0 || start >= size || target >= size {
+        return Err(
+            anyhow!("Start {start} and target {target} must be on a dial of size {size}").into(),
+        );
+    }
+
+    let mut position = start;
+    let mut simulation = Simulation {
+        landings: 0,
+        passes: 0,
+    };
+
+    let clicks = input.iter().map(|rotation| rotation.clicks);
+    context
+        .progress()
+        .start(clicks.fold(0, u64::saturating_add));
+
+    for rotation in input {
+        context.cancelled()?;
+
+        for click in 0..rotation.clicks {
+            // A single rotation can run to billions of clicks, so it's checked on as it turns.
+            if click % CHECK_EVERY == 0 {
+                context.cancelled()?;
+                context
+                    .progress()
+                    .advance(CHECK_EVERY.min(rotation.clicks - click));
+            }
+
+            position = match rotation.direction {
+                Direction::Left => (position + size - 1) % size,
+                Direction::Right => (position + 1) % size,
+            };
+
+            if position == target {
+                simulation.passes += 1;
+            }
+        }
+
+        if position == target {
+            simulation.landings += 1;
+        }
+    }
+
+    Ok(simulation)
+}
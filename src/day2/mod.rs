@@ -1,162 +1,621 @@
 use std::{
+    collections::{BTreeSet, HashSet},
     fmt::{Debug, Display},
+    ops::RangeInclusive,
     path::Path,
 };
 
 use anyhow::Context;
+use serde::Serialize;
+
+use crate::{
+    context::SolveContext,
+    error::Error,
+    params::{ParamKind, ParamSpec},
+    problem::{Assumption, Problem, extent},
+    rng::Rng,
+};
 
-use crate::{cli::RunConfig, error::Error, problem::Problem};
+mod reference;
 
-pub struct Day2<'a> {
-    config: &'a RunConfig,
+pub struct Day2 {
+    rule1: RepetitionRule,
+    rule2: RepetitionRule,
+    dedup: bool,
 }
 
-impl<'a> Problem<'a> for Day2<'a> {
-    type Input = Vec<ProductId>;
-    type Answer1 = usize;
-    type Answer2 = usize;
-
-    fn init(config: &'a RunConfig) -> Self
+impl Problem for Day2 {
+    type Input = Vec<IdRange>;
+    type Answer1 = u128;
+    type Answer2 = u128;
+    type Shared = ();
+
+    const PARAMS: &'static [ParamSpec] = &[
+        ParamSpec {
+            name: "part1-min-repeats",
+            kind: ParamKind::UInt,
+            default: "2",
+            help: "The fewest times a pattern repeats in an invalid ID for part 1",
+            bounds: Some((2, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "part1-max-repeats",
+            kind: ParamKind::OptionalUInt,
+            default: "2",
+            help: "The most times a pattern repeats in an invalid ID for part 1",
+            bounds: Some((2, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "part2-min-repeats",
+            kind: ParamKind::UInt,
+            default: "2",
+            help: "The fewest times a pattern repeats in an invalid ID for part 2",
+            bounds: Some((2, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "part2-max-repeats",
+            kind: ParamKind::OptionalUInt,
+            default: "none",
+            help: "The most times a pattern repeats in an invalid ID for part 2",
+            bounds: Some((2, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "lengths",
+            kind: ParamKind::UIntList,
+            default: "",
+            help: "The digit lengths an invalid ID may have, any length when empty",
+            bounds: Some((1, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "dedup",
+            kind: ParamKind::Bool,
+            default: "false",
+            help: "Count IDs covered by overlapping ranges only once",
+            bounds: None,
+        },
+    ];
+
+    fn init(context: &SolveContext) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Self { config }
+        // The bounds on the parameters keep each within a `u32`.
+        let params = context.params();
+        let lengths = params
+            .uint_list("lengths")
+            .iter()
+            .map(|length| *length as u32)
+            .collect::<Vec<_>>();
+
+        let rule = |part: &str| {
+            let min_repeats = params.uint(&format!("{part}-min-repeats")) as u32;
+            let max_repeats = params
+                .optional_uint(&format!("{part}-max-repeats"))
+                .map(|max| max as u32);
+
+            if let Some(max_repeats) = max_repeats.filter(|max| *max < min_repeats) {
+                return Err(Error::Param(format!(
+                    "{part}-min-repeats {min_repeats} is more than {part}-max-repeats {max_repeats}"
+                )));
+            }
+
+            Ok(RepetitionRule {
+                min_repeats,
+                max_repeats,
+                lengths: (!lengths.is_empty()).then(|| lengths.clone()),
+            })
+        };
+
+        Ok(Self {
+            rule1: rule("part1")?,
+            rule2: rule("part2")?,
+            dedup: params.bool("dedup"),
+        })
     }
 
-    fn parse(&self, content: &str, _path: &Path) -> Result<Self::Input, Error> {
+    fn parse(
+        &self,
+        content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
         content
             .split('\n')
             .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
             .flat_map(|line| line.split(','))
-            .map(ProductId::parse)
-            .flat_map(|result| match result {
-                Ok(vec) => vec.into_iter().map(Ok).collect(),
-                Err(error) => vec![Err(error)],
-            })
+            .filter(|range| !range.trim().is_empty())
+            .map(IdRange::parse)
             .collect()
     }
 
-    fn part1(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
-        let result = input
-            .iter()
-            .filter_map(|id| {
-                let (start, end) = id.split()?;
+    fn part1(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        self.sum_invalid(input, &self.rule1, context)
+    }
 
-                let invalid = start == end;
-                if invalid && self.config.verbose {
-                    println!("ID {id}, start {start}, end {end}");
-                }
+    fn part2(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        self.sum_invalid(input, &self.rule2, context)
+    }
 
-                if !invalid {
-                    return None;
-                }
+    fn reference_part1(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        reference::sum_invalid(input, &self.rule1, self.dedup, context)
+    }
 
-                Some(id.as_usize())
-            })
-            .sum();
+    fn reference_part2(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        reference::sum_invalid(input, &self.rule2, self.dedup, context)
+    }
 
-        Ok(result)
+    fn assumptions(&self, input: &Self::Input) -> Vec<Assumption> {
+        let reversed = input
+            .iter()
+            .enumerate()
+            .find(|(_, range)| range.ids().is_empty())
+            .map(|(index, range)| format!("Range {} is {range}", index + 1));
+
+        vec![Assumption::new(
+            "Every range starts no later than it ends",
+            reversed,
+        )]
     }
 
-    fn part2(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
-        let result = input
+    fn inspect(&self, input: &Self::Input) -> Vec<(&'static str, String)> {
+        let ids = input.iter().flat_map(|range| [range.start, range.end]);
+        let widths = input
             .iter()
-            .filter_map(|id| {
-                id.pivot_points().find_map(|(pivot, (pattern, rest))| {
-                    let mut rest = rest;
+            .map(|range| range.end.saturating_sub(range.start).saturating_add(1));
+        let lengths = input
+            .iter()
+            .flat_map(|range| [digits(range.start), digits(range.end)]);
+
+        vec![
+            ("Ranges", input.len().to_string()),
+            (
+                "Ranges after merging",
+                IdRange::merge(input).len().to_string(),
+            ),
+            ("IDs", extent(ids)),
+            ("Digits per ID", extent(lengths)),
+            ("IDs per range", extent(widths)),
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Result<String, Error> {
+        // Narrow ranges keep the enumerating reference solution usable on generated inputs, so
+        // half of them are placed around a repeated ID to make sure some invalid IDs turn up.
+        let ranges = (0..size)
+            .map(|_| {
+                let length = rng.range(1..=12) as u32;
+                let width = rng.range(0..=1_000);
+                let id = match rng.chance(0.5) {
+                    true => repeated_id(rng, length),
+                    false => rng.range(10_u64.pow(length - 1)..=10_u64.pow(length) - 1),
+                };
+
+                let start = id.saturating_sub(rng.range(0..=width));
+                format!("{start}-{}", start + width)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Ok(ranges)
+    }
+}
+
+/// A random ID of `length` digits made of a shorter pattern repeated, or a single digit when
+/// `length` is `1`.
+fn repeated_id(rng: &mut Rng, length: u32) -> u64 {
+    let periods = (1..length)
+        .filter(|period| length.is_multiple_of(*period))
+        .collect::<Vec<_>>();
+
+    let Some(last) = periods.len().checked_sub(1) else {
+        return rng.range(1..=9);
+    };
 
-                    if self.config.verbose {
-                        println!("\nPattern {pattern}, rest {rest}");
-                    }
+    let period = periods[rng.range(0..=last as u64) as usize];
+    let pattern = rng.range(10_u64.pow(period - 1)..=10_u64.pow(period) - 1);
 
-                    while rest.len() >= pivot {
-                        let (segment, next) = rest.split_at(pivot);
-                        rest = next;
+    (0..length / period).fold(0, |id, _| id * 10_u64.pow(period) + pattern)
+}
 
-                        if self.config.verbose {
-                            println!("Checking {} against {pattern}", segment);
-                        }
+impl Day2 {
+    fn sum_invalid(
+        &self,
+        input: &[IdRange],
+        rule: &RepetitionRule,
+        context: &SolveContext,
+    ) -> Result<u128, Error> {
+        if context.verbose() {
+            self.report(input, rule);
+        }
 
-                        if segment != pattern {
-                            if self.config.verbose {
-                                println!("ID {id} is valid");
-                            }
+        let ranges = match self.dedup {
+            true => IdRange::merge(input),
+            false => input.to_vec(),
+        };
 
-                            return None;
-                        }
-                    }
+        context.progress().start(ranges.len() as u64);
 
-                    if self.config.verbose {
-                        println!("ID {id} is invalid");
-                    }
+        ranges.iter().try_fold(0_u128, |total, range| {
+            context.cancelled()?;
+            let sum = range.sum_repeated(rule)?;
+            context.progress().advance(1);
+            let total = total
+                .checked_add(sum)
+                .context("Sum of invalid IDs overflowed u128")?;
 
-                    Some(id.as_usize())
+            Ok(total)
+        })
+    }
+
+    /// Lists the invalid IDs each range contributes, flagging IDs an earlier range already
+    /// covered when de-duplicating.
+    fn report(&self, input: &[IdRange], rule: &RepetitionRule) {
+        let mut seen = HashSet::new();
+
+        for range in input {
+            let ids = range
+                .repeated(rule)
+                .map(|id| match seen.insert(id) || !self.dedup {
+                    true => id.to_string(),
+                    false => format!("{id} (duplicate)"),
                 })
-            })
-            .sum();
+                .collect::<Vec<_>>();
 
-        Ok(result)
+            match ids.is_empty() {
+                true => println!("{range} has no invalid IDs"),
+                false => println!("{range} has {} invalid IDs: {}", ids.len(), ids.join(", ")),
+            }
+        }
     }
 }
 
-pub struct ProductId(usize, String);
+/// Which IDs count as invalid: those made of a digit pattern repeated some number of times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepetitionRule {
+    /// The fewest times the pattern has to repeat.
+    pub min_repeats: u32,
 
-impl ProductId {
-    pub fn parse(value: &str) -> Result<Vec<Self>, Error> {
-        let mut range = value.split('-');
-        let first = range
-            .next()
-            .with_context(|| "Invalid product ID range, missing first ID")?
-            .parse::<usize>()
-            .with_context(|| "Invalid product ID given in range")?;
+    /// The most times the pattern may repeat, unbounded when `None`.
+    pub max_repeats: Option<u32>,
 
-        let second = range
-            .next()
-            .with_context(|| "Invalid product ID range, missing second ID")?
-            .parse::<usize>()
-            .with_context(|| "Invalid product ID given in range")?;
+    /// The digit lengths an invalid ID may have, any length when `None`.
+    pub lengths: Option<Vec<u32>>,
+}
 
-        Ok((first..=second)
-            .map(|v| ProductId(v, v.to_string()))
-            .collect())
+impl RepetitionRule {
+    /// Whether a `length` digit ID repeating a `period` digit pattern breaks the rule.
+    pub fn accepts(&self, length: u32, period: u32) -> bool {
+        let repeats = length / period;
+
+        length.is_multiple_of(period)
+            && repeats >= self.min_repeats
+            && self.max_repeats.is_none_or(|max| repeats <= max)
+            && self
+                .lengths
+                .as_ref()
+                .is_none_or(|lengths| lengths.contains(&length))
     }
+}
 
-    pub fn split(&self) -> Option<(&str, &str)> {
-        let (pivot, is_even) = (self.1.len() / 2, self.1.len() % 2 == 0);
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IdRange {
+    start: u128,
+    end: u128,
+}
 
-        if !is_even {
-            return None;
-        }
+impl IdRange {
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let (first, second) = value
+            .trim()
+            .split_once('-')
+            .with_context(|| format!("Invalid product ID range '{value}', missing second ID"))?;
+
+        let start = first
+            .parse::<u128>()
+            .with_context(|| format!("Invalid product ID '{first}' given in range"))?;
+
+        let end = second
+            .parse::<u128>()
+            .with_context(|| format!("Invalid product ID '{second}' given in range"))?;
 
-        Some(self.1.split_at(pivot))
+        Ok(Self { start, end })
     }
 
-    pub fn pivot_points(&self) -> impl Iterator<Item = (usize, (&str, &str))> {
-        let length = self.1.len();
-        (2..=self.1.len()).filter_map(move |divisor| {
-            let (pivot, is_even) = (length / divisor, length % divisor == 0);
+    pub fn ids(&self) -> RangeInclusive<u128> {
+        self.start..=self.end
+    }
+
+    /// Combines overlapping and adjacent ranges so each ID is covered at most once.
+    pub fn merge(ranges: &[IdRange]) -> Vec<IdRange> {
+        let mut sorted = ranges.to_vec();
+        sorted.sort_by_key(|range| (range.start, range.end));
 
-            if is_even {
-                return Some((pivot, self.1.split_at(pivot)));
+        sorted.into_iter().fold(vec![], |mut acc, range| {
+            match acc.last_mut() {
+                Some(current) if range.start <= current.end.saturating_add(1) => {
+                    current.end = current.end.max(range.end);
+                }
+                _ => acc.push(range),
             }
 
-            None
+            acc
         })
     }
 
-    pub fn as_usize(&self) -> usize {
-        self.0
+    /// Sums every ID in the range made of a digit pattern repeated as `rule` describes.
+    ///
+    /// Rather than visiting each ID, the candidates are generated per digit length: a `length`
+    /// digit ID repeating a `period` digit pattern is `pattern * 1_0..01_0..01`, so the matching
+    /// patterns form a contiguous run whose sum is an arithmetic series.
+    pub fn sum_repeated(&self, rule: &RepetitionRule) -> Result<u128, Error> {
+        self.lengths()
+            .try_fold(0_u128, |total, (length, low, high)| {
+                let periods = (1..length)
+                    .filter(|period| length.is_multiple_of(*period))
+                    .collect::<Vec<_>>();
+
+                // An ID repeating with periods `a` and `b` also repeats with `gcd(a, b)`, so each
+                // ID is attributed to its smallest period to avoid counting `111111` repeatedly.
+                let mut primitive: Vec<(u32, u128)> = Vec::with_capacity(periods.len());
+                for period in periods {
+                    let repeated = repeated_sum(low, high, length, period)?;
+                    let shorter = primitive
+                        .iter()
+                        .filter(|(smaller, _)| period.is_multiple_of(*smaller))
+                        .map(|(_, sum)| sum)
+                        .sum::<u128>();
+
+                    primitive.push((period, repeated - shorter));
+                }
+
+                primitive
+                    .iter()
+                    .filter(|(smallest, _)| {
+                        (1..length).any(|period| {
+                            period.is_multiple_of(*smallest) && rule.accepts(length, period)
+                        })
+                    })
+                    .try_fold(total, |total, (_, sum)| total.checked_add(*sum))
+                    .context("Sum of invalid IDs overflowed u128")
+                    .map_err(Error::from)
+            })
+    }
+
+    /// Generates the IDs in the range made of a digit pattern repeated as `rule` describes, in
+    /// ascending order.
+    pub fn repeated(&self, rule: &RepetitionRule) -> impl Iterator<Item = u128> {
+        self.lengths().flat_map(|(length, low, high)| {
+            (1..length)
+                .filter(|period| rule.accepts(length, *period))
+                .filter_map(move |period| patterns(low, high, length, period).ok())
+                .flat_map(|(patterns, multiplier)| {
+                    patterns.map(move |pattern| pattern * multiplier)
+                })
+                .collect::<BTreeSet<_>>()
+        })
+    }
+
+    /// Splits the range by digit length, yielding each length with its clamped bounds.
+    fn lengths(&self) -> impl Iterator<Item = (u32, u128, u128)> {
+        let (start, end) = (self.start, self.end);
+
+        (digits(start)..=digits(end)).map(move |length| {
+            let low = start.max(pow10(length - 1).unwrap_or(u128::MAX));
+            let high = end.min(pow10(length).map_or(u128::MAX, |p| p - 1));
+
+            (length, low, high)
+        })
     }
 }
 
-impl Debug for ProductId {
+impl Debug for IdRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ProductId").field(&self.0).finish()
+        f.debug_tuple("IdRange").field(&self.ids()).finish()
     }
 }
 
-impl Display for ProductId {
+impl Display for IdRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Finds the `period` digit patterns which, repeated to `length` digits, land in `low..=high`.
+///
+/// Returns the patterns along with the multiplier that repeats a pattern.
+fn patterns(
+    low: u128,
+    high: u128,
+    length: u32,
+    period: u32,
+) -> Result<(RangeInclusive<u128>, u128), Error> {
+    let base = pow10(period).context("Pattern too long for u128")?;
+    let multiplier = (0..length / period)
+        .try_fold(0_u128, |acc, _| acc.checked_mul(base)?.checked_add(1))
+        .context("Repeated pattern overflowed u128")?;
+
+    let first = low.div_ceil(multiplier).max(base / 10);
+    let last = (high / multiplier).min(base - 1);
+
+    Ok((first..=last, multiplier))
+}
+
+/// Sums the `length` digit IDs in `low..=high` that repeat a `period` digit pattern.
+fn repeated_sum(low: u128, high: u128, length: u32, period: u32) -> Result<u128, Error> {
+    let (patterns, multiplier) = patterns(low, high, length, period)?;
+    let (first, last) = (*patterns.start(), *patterns.end());
+
+    if first > last {
+        return Ok(0);
+    }
+
+    let count = last - first + 1;
+    let series = match count.is_multiple_of(2) {
+        true => (count / 2).checked_mul(first + last),
+        false => count.checked_mul((first + last) / 2),
+    };
+
+    let sum = series
+        .and_then(|series| series.checked_mul(multiplier))
+        .context("Sum of repeated patterns overflowed u128")?;
+
+    Ok(sum)
+}
+
+fn digits(value: u128) -> u32 {
+    value.checked_ilog10().unwrap_or(0) + 1
+}
+
+fn pow10(exp: u32) -> Option<u128> {
+    10_u128.checked_pow(exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{metrics::Metrics, params::Params};
+
+    const EXAMPLE: &str = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,\
+        1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,\
+        2121212118-2121212124";
+
+    fn ranges(value: &str) -> Vec<IdRange> {
+        value
+            .split(',')
+            .map(|range| IdRange::parse(range).unwrap())
+            .collect()
+    }
+
+    fn rule(min_repeats: u32, max_repeats: Option<u32>) -> RepetitionRule {
+        RepetitionRule {
+            min_repeats,
+            max_repeats,
+            lengths: None,
+        }
+    }
+
+    fn sum_repeated(ranges: &[IdRange], rule: &RepetitionRule) -> u128 {
+        ranges
+            .iter()
+            .map(|range| range.sum_repeated(rule).unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn sums_every_repeated_pattern_in_a_length() {
+        // 11 + 22 + ... + 99
+        assert_eq!(repeated_sum(10, 99, 2, 1).unwrap(), 495);
+
+        // 1010 + 1111 + ... + 9999, which is 101 times 10 + 11 + ... + 99
+        assert_eq!(repeated_sum(1000, 9999, 4, 2).unwrap(), 101 * 4905);
+    }
+
+    #[test]
+    fn sums_only_patterns_within_the_bounds() {
+        assert_eq!(repeated_sum(95, 99, 2, 1).unwrap(), 99);
+        assert_eq!(repeated_sum(12, 21, 2, 1).unwrap(), 0);
+        assert_eq!(repeated_sum(1212, 1313, 4, 2).unwrap(), 1212 + 1313);
+    }
+
+    #[test]
+    fn counts_ids_with_several_periods_once() {
+        // 111111 repeats with periods 1, 2 and 3, and 121212 only with period 2.
+        let ranges = ranges("111111-111111,121212-121212");
+
+        assert_eq!(sum_repeated(&ranges, &rule(2, None)), 111111 + 121212);
+        assert_eq!(sum_repeated(&ranges, &rule(2, Some(2))), 111111);
+        assert_eq!(sum_repeated(&ranges, &rule(3, Some(3))), 111111 + 121212);
+        assert_eq!(sum_repeated(&ranges, &rule(6, None)), 111111);
+    }
+
+    #[test]
+    fn agrees_with_generating_each_id() {
+        let ranges = ranges("1-100000,123123-456456,999990-1000010");
+
+        for rule in [
+            rule(2, None),
+            rule(2, Some(2)),
+            rule(3, None),
+            rule(2, Some(4)),
+        ] {
+            let generated = ranges
+                .iter()
+                .flat_map(|range| range.repeated(&rule))
+                .sum::<u128>();
+
+            assert_eq!(sum_repeated(&ranges, &rule), generated, "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let ranges = ranges(EXAMPLE);
+
+        assert_eq!(sum_repeated(&ranges, &rule(2, Some(2))), 1227775554);
+        assert_eq!(sum_repeated(&ranges, &rule(2, None)), 4174379265);
+    }
+
+    fn init(params: &[(&str, &str)]) -> Result<Day2, Error> {
+        let params = params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        let params = Params::resolve(Day2::PARAMS, &params)?;
+
+        Day2::init(&SolveContext::new(
+            params,
+            false,
+            Arc::new(Metrics::start(false)),
+        ))
+    }
+
+    #[test]
+    fn rejects_repeats_out_of_bounds() {
+        for (name, value) in [
+            ("part1-min-repeats", "1"),
+            ("part2-max-repeats", "0"),
+            ("part1-min-repeats", "4294967296"),
+            ("lengths", "0"),
+        ] {
+            let result = init(&[(name, value)]);
+            assert!(matches!(result, Err(Error::Param(_))), "{name}={value}");
+        }
+    }
+
+    #[test]
+    fn rejects_a_minimum_above_the_maximum() {
+        let result = init(&[("part1-min-repeats", "5"), ("part1-max-repeats", "3")]);
+        assert!(matches!(result, Err(Error::Param(_))));
+
+        let result = init(&[("part2-min-repeats", "3"), ("part2-max-repeats", "2")]);
+        assert!(matches!(result, Err(Error::Param(_))));
+    }
+
+    #[test]
+    fn accepts_repeats_within_bounds() {
+        let day = init(&[("part2-min-repeats", "3"), ("part2-max-repeats", "3")]).unwrap();
+
+        assert_eq!(day.rule1, rule(2, Some(2)));
+        assert_eq!(day.rule2, rule(3, Some(3)));
     }
 }
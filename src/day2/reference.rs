@@ -0,0 +1,90 @@
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Display},
+};
+
+use anyhow::Context;
+
+use super::{IdRange, RepetitionRule};
+use crate::{context::SolveContext, error::Error};
+
+/// Sums every ID made of a pattern repeated as `rule` describes by enumerating each range,
+/// remembering the IDs already counted when `dedup` is set so overlapping ranges count once.
+pub fn sum_invalid(
+    input: &[IdRange],
+    rule: &RepetitionRule,
+    dedup: bool,
+    context: &SolveContext,
+) -> Result<u128, Error> {
+    let ids = input.iter().map(|range| range.ids().size_hint().0 as u64);
+    context.progress().start(ids.fold(0, u64::saturating_add));
+
+    let mut seen = HashSet::new();
+    let mut total = 0_u128;
+    for id in input.iter().flat_map(ProductId::enumerate) {
+        context.cancelled()?;
+        context.progress().advance(1);
+
+        if id.repeats(rule, context.verbose()) && (!dedup || seen.insert(id.as_u128())) {
+            total = total
+                .checked_add(id.as_u128())
+                .context("Sum of invalid IDs overflowed u128")?;
+        }
+    }
+
+    Ok(total)
+}
+
+pub struct ProductId(u128, String);
+
+impl ProductId {
+    pub fn enumerate(range: &IdRange) -> impl Iterator<Item = Self> {
+        range.ids().map(|v| ProductId(v, v.to_string()))
+    }
+
+    /// Whether the ID is a shorter pattern repeated some number of times `rule` accepts,
+    /// checking each pattern length that divides the ID.
+    pub fn repeats(&self, rule: &RepetitionRule, verbose: bool) -> bool {
+        let length = self.1.len();
+
+        let invalid = (1..length)
+            .filter(|period| length.is_multiple_of(*period))
+            .filter(|period| rule.accepts(length as u32, *period as u32))
+            .any(|period| {
+                let (pattern, rest) = self.1.split_at(period);
+
+                if verbose {
+                    println!("\nPattern {pattern}, rest {rest}");
+                }
+
+                rest.as_bytes()
+                    .chunks(period)
+                    .all(|segment| segment == pattern.as_bytes())
+            });
+
+        if verbose {
+            match invalid {
+                true => println!("ID {self} is invalid"),
+                false => println!("ID {self} is valid"),
+            }
+        }
+
+        invalid
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Debug for ProductId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ProductId").field(&self.0).finish()
+    }
+}
+
+impl Display for ProductId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
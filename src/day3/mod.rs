@@ -1,82 +1,357 @@
-use std::path::Path;
+use std::{fmt::Display, iter::Sum, ops::Add, path::Path};
 
-use crate::{cli::RunConfig, error::Error, problem::Problem};
+use anyhow::Context;
 
-pub struct Day3;
+use crate::{
+    cli::Part,
+    context::SolveContext,
+    error::Error,
+    params::{ParamKind, ParamSpec},
+    problem::{Assumption, Problem, extent},
+    rng::Rng,
+};
 
-impl<'a> Problem<'a> for Day3 {
-    type Input = Vec<Vec<u64>>;
-    type Answer1 = u64;
-    type Answer2 = u64;
+mod reference;
 
-    fn init(_config: &'a RunConfig) -> Self
+pub struct Day3 {
+    part1_units: usize,
+    part2_units: usize,
+}
+
+impl Problem for Day3 {
+    type Input = Vec<Vec<u8>>;
+    type Answer1 = Joltage;
+    type Answer2 = Joltage;
+    type Shared = ();
+
+    const PARAMS: &'static [ParamSpec] = &[
+        ParamSpec {
+            name: "part1-units",
+            kind: ParamKind::UInt,
+            default: "2",
+            help: "How many batteries to turn on in each bank for part 1",
+            bounds: Some((1, u32::MAX as u64)),
+        },
+        ParamSpec {
+            name: "part2-units",
+            kind: ParamKind::UInt,
+            default: "12",
+            help: "How many batteries to turn on in each bank for part 2",
+            bounds: Some((1, u32::MAX as u64)),
+        },
+    ];
+
+    fn init(context: &SolveContext) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Self
+        let params = context.params();
+
+        Ok(Self {
+            part1_units: params.uint("part1-units") as usize,
+            part2_units: params.uint("part2-units") as usize,
+        })
     }
 
-    fn parse(&self, content: &str, _path: &Path) -> Result<Self::Input, Error> {
-        let input = content
+    fn parse(
+        &self,
+        content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
+        content
             .split('\n')
             .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
             .map(|line| {
                 line.chars()
-                    .map(|char| char.to_digit(10).unwrap() as u64)
+                    .map(|char| {
+                        let digit = char.to_digit(10).with_context(|| {
+                            format!("Invalid battery '{char}' in bank '{line}'")
+                        })?;
+
+                        Ok(digit as u8)
+                    })
                     .collect()
             })
-            .collect();
+            .collect()
+    }
 
-        Ok(input)
+    fn part1(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        total_joltage(input, self.part1_units, context)
     }
 
-    fn part1(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
-        let result = input
-            .iter()
-            .map(|bank| {
-                let (first, second) = bank.iter().rev().skip(2).fold(
-                    (bank[bank.len() - 2], bank[bank.len() - 1]),
-                    |(first, second), digit| {
-                        if first > *digit {
-                            return (first, second);
-                        }
-
-                        (*digit, if second < first { first } else { second })
-                    },
-                );
-
-                (first * 10) + second
-            })
-            .sum();
+    fn part2(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        total_joltage(input, self.part2_units, context)
+    }
+
+    fn reference_part1(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        reference::total_joltage(input, self.part1_units, context)
+    }
 
-        Ok(result)
+    fn reference_part2(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        reference::total_joltage(input, self.part2_units, context)
     }
 
-    fn part2(&self, input: &Self::Input) -> Result<Self::Answer2, Error> {
-        let result = input.iter().map(|bank| select_n_batteries(bank, 12)).sum();
+    fn assumptions(&self, input: &Self::Input) -> Vec<Assumption> {
+        let short = |part: &str, units: usize| {
+            input
+                .iter()
+                .enumerate()
+                .find(|(_, bank)| bank.len() < units)
+                .map(|(index, bank)| {
+                    let length = bank.len();
+                    format!(
+                        "Bank {} has {length} batteries, but {part} turns on {units}",
+                        index + 1
+                    )
+                })
+        };
 
-        Ok(result)
+        vec![
+            Assumption::new(
+                "Every bank has at least as many batteries as part 1 turns on",
+                short("part 1", self.part1_units),
+            )
+            .for_part(Part::One),
+            Assumption::new(
+                "Every bank has at least as many batteries as part 2 turns on",
+                short("part 2", self.part2_units),
+            )
+            .for_part(Part::Two),
+        ]
     }
-}
 
-fn select_n_batteries(bank: &[u64], units: usize) -> u64 {
-    bank.iter()
-        .rev()
-        .skip(units)
-        .fold(bank[bank.len() - units..].to_vec(), |mut acc, digit| {
-            let mut digit = *digit;
-            let mut index = 0;
+    fn inspect(&self, input: &Self::Input) -> Vec<(&'static str, String)> {
+        let lengths = input.iter().map(Vec::len);
+        let digits = input.iter().flatten().copied();
+
+        vec![
+            ("Banks", input.len().to_string()),
+            ("Batteries per bank", extent(lengths)),
+            ("Joltage ratings", extent(digits)),
+        ]
+    }
 
-            while index < acc.len() && acc[index] <= digit {
-                (digit, acc[index]) = (acc[index], digit);
-                index += 1;
-            }
+    fn generate(&self, rng: &mut Rng, size: usize) -> Result<String, Error> {
+        // Every bank needs at least as many batteries as either part turns on, and a few more to
+        // leave a choice, but not so many the reference can't try every choice.
+        let units = self.part1_units.max(self.part2_units).max(1) as u64;
+        let banks = (0..size)
+            .map(|_| {
+                let length = rng.range(units..=units.saturating_add(6));
+                (0..length)
+                    .map(|_| char::from(b'0' + rng.range(1..=9) as u8))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
 
-            acc
-        })
+        Ok(banks)
+    }
+}
+
+fn total_joltage(
+    banks: &[Vec<u8>],
+    units: usize,
+    context: &SolveContext,
+) -> Result<Joltage, Error> {
+    banks
         .iter()
-        .rev()
-        .enumerate()
-        .map(|(exp, digit)| digit * 10_u64.pow(exp as u32))
+        .map(|bank| {
+            context.cancelled()?;
+            Ok(select_n_batteries(bank, units))
+        })
         .sum()
 }
+
+/// Picks the `units` batteries, in bank order, that produce the largest joltage.
+///
+/// Walks the bank once keeping a stack of chosen digits, dropping any smaller digit on top of the
+/// stack while enough of the bank remains to still fill all `units` slots.
+fn select_n_batteries(bank: &[u8], units: usize) -> Joltage {
+    let mut stack = Vec::with_capacity(units);
+
+    for (index, digit) in bank.iter().enumerate() {
+        let remaining = bank.len() - index;
+
+        while stack
+            .last()
+            .is_some_and(|top| top < digit && stack.len() + remaining > units)
+        {
+            stack.pop();
+        }
+
+        if stack.len() < units {
+            stack.push(*digit);
+        }
+    }
+
+    Joltage::from_digits(&stack)
+}
+
+/// An arbitrarily large, non-negative decimal joltage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Joltage {
+    /// The decimal digits, least significant first, without trailing zeros.
+    digits: Vec<u8>,
+}
+
+impl Joltage {
+    /// Builds a joltage from its decimal digits, most significant first.
+    pub fn from_digits(digits: &[u8]) -> Self {
+        let mut digits = digits.iter().rev().copied().collect::<Vec<_>>();
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Self { digits }
+    }
+}
+
+impl Add for Joltage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let length = self.digits.len().max(rhs.digits.len());
+        let mut digits = Vec::with_capacity(length + 1);
+        let mut carry = 0;
+
+        for index in 0..length {
+            let sum = self.digits.get(index).unwrap_or(&0) + rhs.digits.get(index).unwrap_or(&0);
+            let sum = sum + carry;
+            digits.push(sum % 10);
+            carry = sum / 10;
+        }
+
+        if carry > 0 {
+            digits.push(carry);
+        }
+
+        Self { digits }
+    }
+}
+
+impl Sum for Joltage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Display for Joltage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+
+        self.digits
+            .iter()
+            .rev()
+            .try_for_each(|digit| write!(f, "{digit}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{metrics::Metrics, params::Params};
+
+    fn joltage(value: &str) -> Joltage {
+        let digits = value.bytes().map(|digit| digit - b'0').collect::<Vec<_>>();
+        Joltage::from_digits(&digits)
+    }
+
+    #[test]
+    fn adds_with_carries() {
+        assert_eq!(joltage("999") + joltage("1"), joltage("1000"));
+        assert_eq!(joltage("1") + joltage("999"), joltage("1000"));
+        assert_eq!(joltage("123") + joltage("877"), joltage("1000"));
+    }
+
+    #[test]
+    fn adds_beyond_u128() {
+        let max = u128::MAX.to_string();
+        let sum = joltage(&max) + joltage(&max);
+
+        assert_eq!(sum.to_string(), "680564733841876926926749214863536422910");
+    }
+
+    #[test]
+    fn adds_zero() {
+        assert_eq!(joltage("0") + joltage("42"), joltage("42"));
+        assert_eq!(Joltage::default() + Joltage::default(), Joltage::default());
+        assert_eq!(Joltage::default().to_string(), "0");
+    }
+
+    #[test]
+    fn ignores_leading_zeros() {
+        assert_eq!(joltage("00120"), joltage("120"));
+        assert_eq!(joltage("00120").to_string(), "120");
+    }
+
+    #[test]
+    fn sums_every_joltage() {
+        let sum = ["98", "89", "78", "92"]
+            .into_iter()
+            .map(joltage)
+            .sum::<Joltage>();
+
+        assert_eq!(sum, joltage("357"));
+    }
+
+    fn context() -> SolveContext {
+        let params = Params::resolve(Day3::PARAMS, &[]).unwrap();
+        SolveContext::new(params, false, Arc::new(Metrics::start(false)))
+    }
+
+    fn banks(value: &str) -> Vec<Vec<u8>> {
+        value
+            .split(',')
+            .map(|bank| bank.bytes().map(|digit| digit - b'0').collect())
+            .collect()
+    }
+
+    #[test]
+    fn matches_the_reference() {
+        let banks = banks("987654321111111,811111111111119,234234234234278,818181911112111");
+        let context = context();
+
+        for units in [1, 2, 3, 12, 15] {
+            let optimised = total_joltage(&banks, units, &context).unwrap();
+            let reference = reference::total_joltage(&banks, units, &context).unwrap();
+
+            assert_eq!(optimised, reference, "{units} units");
+        }
+    }
+
+    #[test]
+    fn uses_every_battery_of_a_short_bank() {
+        let banks = banks("3,21,987,1");
+        let context = context();
+
+        let optimised = total_joltage(&banks, 5, &context).unwrap();
+        let reference = reference::total_joltage(&banks, 5, &context).unwrap();
+
+        assert_eq!(optimised, joltage("1012"));
+        assert_eq!(reference, optimised);
+    }
+}
@@ -0,0 +1,55 @@
+use super::Joltage;
+use crate::{context::SolveContext, error::Error};
+
+/// Finds the largest joltage from `units` batteries of every bank by trying every way of
+/// choosing them.
+pub fn total_joltage(
+    banks: &[Vec<u8>],
+    units: usize,
+    context: &SolveContext,
+) -> Result<Joltage, Error> {
+    context.progress().start(banks.len() as u64);
+
+    banks
+        .iter()
+        .map(|bank| {
+            let joltage = largest(bank, units, context)?;
+            context.progress().advance(1);
+            Ok(joltage)
+        })
+        .sum()
+}
+
+/// The largest number made of `units` digits of `bank` kept in order, out of every choice of
+/// them. A bank too short has only the one choice of all its digits, as in the optimised solution.
+fn largest(bank: &[u8], units: usize, context: &SolveContext) -> Result<Joltage, Error> {
+    let units = units.min(bank.len());
+    let last = bank.len() - units;
+
+    // The positions of the chosen digits, starting from the first `units` and moving through
+    // every combination in order. As every choice has the same number of digits, comparing the
+    // digit lists compares the numbers.
+    let mut chosen = (0..units).collect::<Vec<_>>();
+    let mut best = vec![];
+    loop {
+        context.cancelled()?;
+
+        let digits = chosen.iter().map(|index| bank[*index]).collect::<Vec<_>>();
+        best = best.max(digits);
+
+        // Move on the last position that can still move, packing those after it in behind.
+        let Some(position) = (0..units)
+            .rev()
+            .find(|position| chosen[*position] < last + position)
+        else {
+            break;
+        };
+
+        chosen[position] += 1;
+        for next in position + 1..units {
+            chosen[next] = chosen[next - 1] + 1;
+        }
+    }
+
+    Ok(Joltage::from_digits(&best))
+}
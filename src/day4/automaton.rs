@@ -0,0 +1,406 @@
+use std::{
+    collections::{BTreeSet, HashMap, hash_map::DefaultHasher},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+};
+
+use colored::Colorize;
+
+use crate::{cli::SimulateConfig, error::Error};
+
+use super::BitGrid;
+
+const PADDING: &str = "   ";
+
+/// Which surrounding cells count as neighbours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Neighbourhood {
+    /// Every cell within the radius, diagonals included
+    #[default]
+    Moore,
+
+    /// Every cell within the radius by Manhattan distance
+    VonNeumann,
+}
+
+impl Neighbourhood {
+    /// The row and column offsets of each neighbour within `radius` on a `width` by `height` grid.
+    pub fn offsets(&self, radius: usize, width: usize, height: usize) -> Vec<(isize, isize)> {
+        // Every cell of the grid is within `width + height` of every other, so larger radii only
+        // add offsets that land outside it, or on cells nearer offsets reach when it wraps.
+        let radius = isize::try_from(radius.min(width + height)).unwrap_or(isize::MAX);
+
+        (-radius..=radius)
+            .flat_map(|dr| (-radius..=radius).map(move |dc| (dr, dc)))
+            .filter(|offset| *offset != (0, 0))
+            .filter(|(dr, dc)| match self {
+                Self::Moore => true,
+                Self::VonNeumann => dr.abs() + dc.abs() <= radius,
+            })
+            .collect()
+    }
+}
+
+/// What happens to neighbours that fall off the side of the grid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Edges {
+    /// Cells outside the grid are always empty
+    #[default]
+    Bounded,
+
+    /// The grid wraps around, so the left edge neighbours the right
+    Toroidal,
+}
+
+/// Decides the next state of a cell from how many of its neighbours are occupied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Occupied cells stay occupied with a neighbour count in this range.
+    pub survive: RangeInclusive<usize>,
+
+    /// Empty cells become occupied with a neighbour count in this range, if any.
+    pub birth: Option<RangeInclusive<usize>>,
+}
+
+impl Rule {
+    pub fn next(&self, occupied: bool, neighbours: usize) -> bool {
+        match occupied {
+            true => self.survive.contains(&neighbours),
+            false => self
+                .birth
+                .as_ref()
+                .is_some_and(|birth| birth.contains(&neighbours)),
+        }
+    }
+}
+
+/// Parses a neighbour count threshold such as `4`, `2-3` or `4-` (four or more).
+pub fn parse_counts(value: &str) -> Result<RangeInclusive<usize>, Error> {
+    let parse = |count: &str| {
+        count
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| Error::Param(format!("Invalid neighbour count '{count}'")))
+    };
+
+    match value.split_once('-') {
+        Some((start, "")) => Ok(parse(start)?..=usize::MAX),
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            match start <= end {
+                true => Ok(start..=end),
+                false => Err(Error::Param(format!(
+                    "Neighbour count range '{value}' ends before it starts"
+                ))),
+            }
+        }
+        None => parse(value).map(|count| count..=count),
+    }
+}
+
+/// How a simulation came to an end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    /// A generation changed nothing.
+    Stable { generation: usize },
+
+    /// The configuration seen at generation `start` came back every `period` generations.
+    Cycle { start: usize, period: usize },
+
+    /// The requested number of generations ran without settling.
+    Limit,
+}
+
+impl Display for End {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stable { generation } => write!(f, "stable from generation {generation}"),
+            Self::Cycle { start, period } => {
+                write!(
+                    f,
+                    "repeats every {period} generations from generation {start}"
+                )
+            }
+            Self::Limit => write!(f, "still changing"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub generations: usize,
+    pub died: usize,
+    pub born: usize,
+    pub end: End,
+}
+
+/// A two state cellular automaton over the warehouse grid.
+pub struct Automaton {
+    cells: BitGrid,
+    offsets: Vec<(isize, isize)>,
+    rule: Rule,
+    edges: Edges,
+    verbose: bool,
+}
+
+impl Automaton {
+    pub fn new(
+        input: &[Vec<char>],
+        neighbourhood: Neighbourhood,
+        radius: usize,
+        rule: Rule,
+        edges: Edges,
+        verbose: bool,
+    ) -> Self {
+        let height = input.len();
+        let width = input.iter().map(Vec::len).max().unwrap_or_default();
+
+        let mut cells = BitGrid::new(width, height);
+        for (row, line) in input.iter().enumerate() {
+            for (column, cell) in line.iter().enumerate() {
+                if *cell == '@' {
+                    cells.set(row * width + column);
+                }
+            }
+        }
+
+        let offsets = match edges {
+            Edges::Bounded => neighbourhood.offsets(radius, width, height),
+            Edges::Toroidal => wrapped_offsets(neighbourhood, radius, width, height),
+        };
+
+        Self {
+            cells,
+            offsets,
+            rule,
+            edges,
+            verbose,
+        }
+    }
+
+    pub fn alive(&self) -> usize {
+        self.cells.count()
+    }
+
+    /// Advances a single generation, returning how many cells died and were born.
+    pub fn step(&mut self) -> (usize, usize) {
+        let (width, height) = (self.cells.width, self.cells.height);
+        let mut next = BitGrid::new(width, height);
+        let (mut died, mut born) = (0, 0);
+
+        for index in 0..width * height {
+            let occupied = self.cells.get(index);
+            let neighbours = self.neighbours(index);
+
+            match (occupied, self.rule.next(occupied, neighbours)) {
+                (true, true) => next.set(index),
+                (true, false) => died += 1,
+                (false, true) => {
+                    next.set(index);
+                    born += 1;
+                }
+                (false, false) => {}
+            }
+        }
+
+        self.cells = next;
+        (died, born)
+    }
+
+    /// Steps until the grid settles, repeats an earlier configuration, or `generations` run.
+    ///
+    /// Only a hash of each generation is kept, so a repeated hash is confirmed by replaying the
+    /// earlier generation from the initial grid and comparing it with the current one.
+    pub fn run(&mut self, generations: Option<usize>) -> Outcome {
+        let initial = self.cells.clone();
+        let mut seen = HashMap::from([(hash(&self.cells), 0)]);
+        let (mut died, mut born) = (0, 0);
+        let mut generation = 0;
+
+        let end = loop {
+            if generations.is_some_and(|limit| generation >= limit) {
+                break End::Limit;
+            }
+
+            let (step_died, step_born) = self.step();
+            generation += 1;
+            (died, born) = (died + step_died, born + step_born);
+
+            if self.verbose {
+                println!("Generation {generation}:\n{self}\n");
+            }
+
+            if step_died == 0 && step_born == 0 {
+                break End::Stable {
+                    generation: generation - 1,
+                };
+            }
+
+            let start = *seen.entry(hash(&self.cells)).or_insert(generation);
+            if start != generation && self.replay(&initial, start).words == self.cells.words {
+                break End::Cycle {
+                    start,
+                    period: generation - start,
+                };
+            }
+        };
+
+        Outcome {
+            generations: generation,
+            died,
+            born,
+            end,
+        }
+    }
+
+    /// The grid `generations` on from `initial`.
+    fn replay(&self, initial: &BitGrid, generations: usize) -> BitGrid {
+        let mut automaton = Self {
+            cells: initial.clone(),
+            offsets: self.offsets.clone(),
+            rule: self.rule.clone(),
+            edges: self.edges,
+            verbose: false,
+        };
+
+        for _ in 0..generations {
+            automaton.step();
+        }
+
+        automaton.cells
+    }
+
+    fn neighbours(&self, index: usize) -> usize {
+        let (width, height) = (self.cells.width as isize, self.cells.height as isize);
+        let (row, column) = (index as isize / width, index as isize % width);
+
+        self.offsets
+            .iter()
+            .filter_map(|(dr, dc)| {
+                let (row, column) = (row + dr, column + dc);
+                match self.edges {
+                    Edges::Toroidal => Some((row.rem_euclid(height), column.rem_euclid(width))),
+                    Edges::Bounded
+                        if (0..height).contains(&row) && (0..width).contains(&column) =>
+                    {
+                        Some((row, column))
+                    }
+                    Edges::Bounded => None,
+                }
+            })
+            .filter(|(row, column)| self.cells.get((row * width + column) as usize))
+            .count()
+    }
+}
+
+/// The offsets of each neighbour within `radius` on a `width` by `height` torus, where offsets
+/// past the size of the grid wrap onto cells nearer offsets reach, or onto the cell itself.
+/// Each is reduced onto the grid so every neighbouring cell is counted once.
+fn wrapped_offsets(
+    neighbourhood: Neighbourhood,
+    radius: usize,
+    width: usize,
+    height: usize,
+) -> Vec<(isize, isize)> {
+    let offsets = neighbourhood.offsets(radius, width, height);
+    let (width, height) = (width.max(1) as isize, height.max(1) as isize);
+
+    offsets
+        .into_iter()
+        .map(|(dr, dc)| (dr.rem_euclid(height), dc.rem_euclid(width)))
+        .filter(|offset| *offset != (0, 0))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn hash(cells: &BitGrid) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    cells.words.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Display for Automaton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = (0..self.cells.height).map(|row| {
+            (0..self.cells.width)
+                .map(
+                    |column| match self.cells.get(row * self.cells.width + column) {
+                        true => '@',
+                        false => '.',
+                    },
+                )
+                .collect::<String>()
+        });
+
+        write!(f, "{}", rows.collect::<Vec<_>>().join("\n"))
+    }
+}
+
+/// Runs the automaton described by `config` over the warehouse and reports how it ended.
+pub fn simulate(config: &SimulateConfig, input: &[Vec<char>]) -> Result<(), Error> {
+    let rule = Rule {
+        survive: config.survive.clone(),
+        birth: config.birth.clone(),
+    };
+
+    let mut automaton = Automaton::new(
+        input,
+        config.neighbourhood,
+        config.radius,
+        rule,
+        config.edges,
+        config.run.verbose,
+    );
+
+    println!("🎄 {}", "Day 4 simulation".bold());
+    let outcome = automaton.run(config.generations);
+
+    println!(
+        "{PADDING}Generations: {}",
+        outcome.generations.to_string().green()
+    );
+    println!("{PADDING}Died:        {}", outcome.died.to_string().green());
+    println!("{PADDING}Born:        {}", outcome.born.to_string().green());
+    println!(
+        "{PADDING}Alive:       {}",
+        automaton.alive().to_string().green()
+    );
+    println!("{PADDING}Outcome:     {}", outcome.end.to_string().yellow());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_single_count() {
+        assert_eq!(parse_counts("4").unwrap(), 4..=4);
+        assert_eq!(parse_counts(" 0 ").unwrap(), 0..=0);
+    }
+
+    #[test]
+    fn parses_a_range_of_counts() {
+        assert_eq!(parse_counts("2-3").unwrap(), 2..=3);
+        assert_eq!(parse_counts("3-3").unwrap(), 3..=3);
+        assert_eq!(parse_counts("2 - 3").unwrap(), 2..=3);
+    }
+
+    #[test]
+    fn parses_an_open_range_of_counts() {
+        assert_eq!(parse_counts("4-").unwrap(), 4..=usize::MAX);
+    }
+
+    #[test]
+    fn rejects_invalid_counts() {
+        for value in ["", "-", "-3", "x", "2-x", "1.5", "3-2"] {
+            assert!(
+                matches!(parse_counts(value), Err(Error::Param(_))),
+                "'{value}'"
+            );
+        }
+    }
+}
@@ -1,24 +1,55 @@
-use std::path::Path;
+use std::{collections::VecDeque, path::Path};
 
-use crate::{cli::RunConfig, error::Error, problem::Problem};
+use crate::{
+    context::SolveContext,
+    error::Error,
+    params::{ParamKind, ParamSpec},
+    problem::{Assumption, Problem},
+    rng::Rng,
+};
 
-pub struct Day4<'a> {
-    config: &'a RunConfig,
+use reference::World;
+
+pub mod automaton;
+mod reference;
+
+pub struct Day4 {
+    /// A roll can be reached by a forklift when fewer than this many rolls surround it.
+    threshold: u8,
 }
 
-impl<'a> Problem<'a> for Day4<'a> {
+impl Problem for Day4 {
     type Input = Vec<Vec<char>>;
     type Answer1 = usize;
     type Answer2 = usize;
+    type Shared = FirstGeneration;
+
+    const PARAMS: &'static [ParamSpec] = &[ParamSpec {
+        name: "threshold",
+        kind: ParamKind::UInt,
+        default: "4",
+        help: "A roll is accessible when fewer than this many rolls surround it",
+        bounds: Some((0, u8::MAX as u64)),
+    }];
 
-    fn init(config: &'a RunConfig) -> Self
+    fn init(context: &SolveContext) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Self { config }
+        // The bounds on the threshold keep it within a `u8`.
+        let threshold = context.params().uint("threshold");
+
+        Ok(Self {
+            threshold: threshold as u8,
+        })
     }
 
-    fn parse(&self, content: &str, _path: &Path) -> Result<Self::Input, Error> {
+    fn parse(
+        &self,
+        content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
         let input = content
             .split('\n')
             .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
@@ -28,108 +59,315 @@ impl<'a> Problem<'a> for Day4<'a> {
         Ok(input)
     }
 
-    fn part1(&self, input: &Self::Input) -> Result<Self::Answer1, Error> {
-        let mut world = World::new(input, self.config.verbose);
+    fn share(&self, input: &Self::Input, context: &SolveContext) -> Result<Self::Shared, Error> {
+        let warehouse = context.span("count neighbours", || Warehouse::new(input, self.threshold));
+        let accessible = warehouse.accessible();
+
+        Ok(FirstGeneration {
+            warehouse,
+            accessible,
+        })
+    }
+
+    fn part1(
+        &self,
+        _input: &Self::Input,
+        shared: &Self::Shared,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        context.trace(|| shared.warehouse.render(&shared.accessible));
+
+        Ok(shared.accessible.count())
+    }
+
+    fn part2(
+        &self,
+        _input: &Self::Input,
+        shared: &Self::Shared,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        let mut warehouse = shared.warehouse.clone();
+        let removed = context.span("remove", || {
+            warehouse.remove_accessible(&shared.accessible, context)
+        })?;
+        context.trace(|| warehouse.render(&removed));
+
+        Ok(removed.count())
+    }
+
+    fn reference_part1(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        let mut world = World::new(input, self.threshold, context.verbose());
         let count = world.update();
 
         Ok(count)
     }
 
-    fn part2(&self, input: &Self::Input) -> Result<Self::Answer2, Error> {
-        let mut world = World::new(input, self.config.verbose);
-        let mut count = world.update();
+    fn reference_part2(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        // Every roll could be removed, so progress is measured against all of them and the
+        // bar finishes early when some rolls stay.
+        let rolls = input.iter().flatten().filter(|cell| **cell == '@').count();
+        context.progress().start(rolls as u64);
+
+        let mut world = World::new(input, self.threshold, context.verbose());
+        let mut count = context.span("generation", || world.update());
         let mut total = count;
+        context.progress().advance(count as u64);
 
         while count > 0 {
-            count = world.update();
+            context.cancelled()?;
+            count = context.span("generation", || world.update());
             total += count;
+            context.progress().advance(count as u64);
         }
 
         Ok(total)
     }
+
+    fn assumptions(&self, input: &Self::Input) -> Vec<Assumption> {
+        let width = input.first().map(Vec::len).unwrap_or_default();
+        let ragged = input
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != width)
+            .map(|(index, row)| {
+                let length = row.len();
+                format!(
+                    "Row {} is {length} cells wide, but the first is {width}",
+                    index + 1
+                )
+            });
+
+        let unknown = input.iter().enumerate().find_map(|(row, cells)| {
+            cells
+                .iter()
+                .position(|cell| !matches!(cell, '@' | '.'))
+                .map(|column| {
+                    let cell = cells[column];
+                    format!("Row {}, column {} is '{cell}'", row + 1, column + 1)
+                })
+        });
+
+        vec![
+            Assumption::new("Every row is as wide as the first", ragged),
+            Assumption::new("Every cell is either a roll '@' or empty '.'", unknown),
+        ]
+    }
+
+    fn inspect(&self, input: &Self::Input) -> Vec<(&'static str, String)> {
+        let width = input.iter().map(Vec::len).max().unwrap_or_default();
+        let cells = width * input.len();
+        let rolls = input.iter().flatten().filter(|cell| **cell == '@').count();
+        let density = rolls as f64 / cells.max(1) as f64 * 100.0;
+
+        vec![
+            ("Grid", format!("{width} x {}", input.len())),
+            ("Rolls", format!("{rolls} of {cells} cells ({density:.1}%)")),
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Result<String, Error> {
+        let grid = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| if rng.chance(0.7) { '@' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(grid)
+    }
 }
 
-struct World {
-    map: Vec<Vec<char>>,
-    count: usize,
-    verbose: bool,
+/// The warehouse before any rolls are removed, along with the rolls part 1 finds accessible, which
+/// part 2 removes first.
+#[derive(Default)]
+pub struct FirstGeneration {
+    warehouse: Warehouse,
+    accessible: BitGrid,
 }
 
-impl World {
-    pub fn new(world: &[Vec<char>], verbose: bool) -> Self {
+/// The rolls of paper in the warehouse, along with how many rolls neighbour each cell.
+///
+/// Removing a roll only changes the counts of its eight neighbours, so rather than rescanning the
+/// whole map each generation, rolls are queued as soon as their count drops below the threshold.
+/// As counts only ever decrease, the rolls removed this way match the generational approach.
+#[derive(Clone, Default)]
+struct Warehouse {
+    rolls: BitGrid,
+    neighbours: Vec<u8>,
+    threshold: u8,
+}
+
+impl Warehouse {
+    pub fn new(input: &[Vec<char>], threshold: u8) -> Self {
+        let height = input.len();
+        let width = input.iter().map(Vec::len).max().unwrap_or_default();
+
+        let mut rolls = BitGrid::new(width, height);
+        for (row, line) in input.iter().enumerate() {
+            for (column, cell) in line.iter().enumerate() {
+                if *cell == '@' {
+                    rolls.set(row * width + column);
+                }
+            }
+        }
+
+        let neighbours = (0..width * height)
+            .map(|index| {
+                rolls
+                    .neighbours(index)
+                    .filter(|neighbour| rolls.get(*neighbour))
+                    .count() as u8
+            })
+            .collect();
+
         Self {
-            map: world.to_vec(),
-            count: 0,
-            verbose,
+            rolls,
+            neighbours,
+            threshold,
         }
     }
 
-    pub fn update(&mut self) -> usize {
-        let map = self
-            .map
-            .iter()
-            .enumerate()
-            .map(|(row_index, row)| {
-                if self.verbose {
-                    println!();
+    /// The rolls a forklift can currently reach.
+    pub fn accessible(&self) -> BitGrid {
+        let mut accessible = BitGrid::new(self.rolls.width, self.rolls.height);
+        for index in self.rolls.iter() {
+            if self.neighbours[index] < self.threshold {
+                accessible.set(index);
+            }
+        }
+
+        accessible
+    }
+
+    /// Removes rolls, starting with the `accessible` ones, until none are accessible, returning
+    /// every roll removed.
+    pub fn remove_accessible(
+        &mut self,
+        accessible: &BitGrid,
+        context: &SolveContext,
+    ) -> Result<BitGrid, Error> {
+        let mut removed = BitGrid::new(self.rolls.width, self.rolls.height);
+        let mut queue = accessible.iter().collect::<VecDeque<_>>();
+
+        while let Some(index) = queue.pop_front() {
+            context.cancelled()?;
+            self.rolls.clear(index);
+            removed.set(index);
+
+            for neighbour in self.rolls.neighbours(index) {
+                if !self.rolls.get(neighbour) {
+                    continue;
                 }
 
-                row.iter()
-                    .map(|cell| if *cell == 'x' { '.' } else { *cell })
-                    .enumerate()
-                    .map(|(column_index, cell)| {
-                        if cell == '.' {
-                            if self.verbose {
-                                print!("{cell}");
-                            }
+                self.neighbours[neighbour] -= 1;
 
-                            return cell;
-                        }
+                // Only queue on the step below the threshold so each roll is queued once.
+                if Some(self.neighbours[neighbour]) == self.threshold.checked_sub(1) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
 
-                        let count = check_cell(&self.map, row_index - 1, column_index - 1)
-                            + check_cell(&self.map, row_index - 1, column_index)
-                            + check_cell(&self.map, row_index - 1, column_index + 1)
-                            + check_cell(&self.map, row_index, column_index + 1)
-                            + check_cell(&self.map, row_index + 1, column_index + 1)
-                            + check_cell(&self.map, row_index + 1, column_index)
-                            + check_cell(&self.map, row_index + 1, column_index - 1)
-                            + check_cell(&self.map, row_index, column_index - 1);
-
-                        let cell = match count {
-                            0..4 => 'x',
-                            _ => cell,
-                        };
-
-                        if self.verbose {
-                            print!("{cell}");
-                        }
+        Ok(removed)
+    }
 
-                        cell
+    /// Draws the warehouse with the `marked` cells shown as `x`.
+    pub fn render(&self, marked: &BitGrid) -> String {
+        (0..self.rolls.height)
+            .map(|row| {
+                (0..self.rolls.width)
+                    .map(|column| {
+                        let index = row * self.rolls.width + column;
+                        match (marked.get(index), self.rolls.get(index)) {
+                            (true, _) => 'x',
+                            (false, true) => '@',
+                            (false, false) => '.',
+                        }
                     })
-                    .collect::<Vec<_>>()
+                    .collect::<String>()
             })
-            .collect::<Vec<_>>();
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
-        if self.verbose {
-            println!();
+/// A fixed size grid of bits, stored row by row.
+#[derive(Clone, Default)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            words: vec![0; (width * height).div_ceil(64)],
         }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
 
-        let count = map
+    pub fn count(&self) -> usize {
+        self.words
             .iter()
-            .map(|row| row.iter().filter(|c| **c == 'x').count())
-            .sum();
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
 
-        self.count = count;
-        self.map = map;
+    /// The indices of every set bit, in order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                let mut word = *word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
 
-        count
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(word_index * 64 + bit)
+                })
+            })
     }
-}
 
-fn check_cell(cells: &[Vec<char>], row: usize, column: usize) -> u32 {
-    cells
-        .get(row)
-        .and_then(|r| r.get(column))
-        .map(|c| if *c == '@' { 1 } else { 0 })
-        .unwrap_or_default()
+    /// The indices of the up to eight cells surrounding `index`.
+    pub fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (row, column) = (index / self.width, index % self.width);
+        let (width, height) = (self.width, self.height);
+
+        (-1_isize..=1)
+            .flat_map(|dr| (-1_isize..=1).map(move |dc| (dr, dc)))
+            .filter(|offset| *offset != (0, 0))
+            .filter_map(move |(dr, dc)| {
+                let row = row.checked_add_signed(dr).filter(|row| *row < height)?;
+                let column = column.checked_add_signed(dc).filter(|col| *col < width)?;
+                Some(row * width + column)
+            })
+    }
 }
@@ -0,0 +1,96 @@
+/// The original generation-by-generation solution, rescanning the whole map each generation.
+pub struct World {
+    map: Vec<Vec<char>>,
+    count: usize,
+    threshold: u32,
+    verbose: bool,
+}
+
+impl World {
+    pub fn new(world: &[Vec<char>], threshold: u8, verbose: bool) -> Self {
+        Self {
+            map: world.to_vec(),
+            count: 0,
+            threshold: threshold as u32,
+            verbose,
+        }
+    }
+
+    pub fn update(&mut self) -> usize {
+        let map = self
+            .map
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                if self.verbose {
+                    println!();
+                }
+
+                row.iter()
+                    .map(|cell| if *cell == 'x' { '.' } else { *cell })
+                    .enumerate()
+                    .map(|(column_index, cell)| {
+                        if cell == '.' {
+                            if self.verbose {
+                                print!("{cell}");
+                            }
+
+                            return cell;
+                        }
+
+                        let count = check_cell(
+                            &self.map,
+                            row_index.wrapping_sub(1),
+                            column_index.wrapping_sub(1),
+                        ) + check_cell(
+                            &self.map,
+                            row_index.wrapping_sub(1),
+                            column_index,
+                        ) + check_cell(
+                            &self.map,
+                            row_index.wrapping_sub(1),
+                            column_index + 1,
+                        ) + check_cell(&self.map, row_index, column_index + 1)
+                            + check_cell(&self.map, row_index + 1, column_index + 1)
+                            + check_cell(&self.map, row_index + 1, column_index)
+                            + check_cell(&self.map, row_index + 1, column_index.wrapping_sub(1))
+                            + check_cell(&self.map, row_index, column_index.wrapping_sub(1));
+
+                        let cell = match count < self.threshold {
+                            true => 'x',
+                            false => cell,
+                        };
+
+                        if self.verbose {
+                            print!("{cell}");
+                        }
+
+                        cell
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        if self.verbose {
+            println!();
+        }
+
+        let count = map
+            .iter()
+            .map(|row| row.iter().filter(|c| **c == 'x').count())
+            .sum();
+
+        self.count = count;
+        self.map = map;
+
+        count
+    }
+}
+
+fn check_cell(cells: &[Vec<char>], row: usize, column: usize) -> u32 {
+    cells
+        .get(row)
+        .and_then(|r| r.get(column))
+        .map(|c| if *c == '@' { 1 } else { 0 })
+        .unwrap_or_default()
+}
@@ -1,86 +1,415 @@
-use std::{ops::RangeInclusive, path::Path};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
+    iter,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
-use crate::{cli::RunConfig, error::Error, problem::Problem};
+use anyhow::{Context, anyhow};
+use colored::Colorize;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+
+use crate::{
+    cli::QueryConfig,
+    context::SolveContext,
+    error::Error,
+    problem::{Problem, extent},
+    rng::Rng,
+};
+
+mod reference;
 
 pub struct Day5;
 
-impl<'a> Problem<'a> for Day5 {
-    type Input = (Vec<RangeInclusive<u64>>, Vec<u64>);
+impl Problem for Day5 {
+    type Input = Inventory;
     type Answer1 = usize;
     type Answer2 = usize;
+    type Shared = ();
 
-    fn init(_config: &'a RunConfig) -> Self
+    fn init(_context: &SolveContext) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Self
-    }
-
-    fn parse(&self, content: &str, _path: &Path) -> Result<Self::Input, Error> {
-        let input = content
-            .split('\n')
-            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-            .fold((vec![], vec![]), |(mut ranges, mut ids), line| {
-                match line.split_once('-') {
-                    Some((first, second)) => {
-                        let first = first.parse().unwrap();
-                        let second = second.parse().unwrap();
-                        ranges.push(first..=second);
-                    }
-                    None => {
-                        ids.push(line.parse().unwrap());
-                    }
+        Ok(Self)
+    }
+
+    fn parse(
+        &self,
+        content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
+        let mut scan = Scan::default();
+        for line in content.split_inclusive('\n') {
+            scan.read(line)?;
+        }
+
+        let ids = content[scan.ids() as usize..].to_string();
+        Ok(Inventory::new(scan.ranges, Ids::Text(ids)))
+    }
+
+    fn parse_file(
+        &self,
+        path: &Path,
+        context: &SolveContext,
+    ) -> Option<Result<Self::Input, Error>> {
+        // Only the ranges are kept, the IDs are checked here and read again as the parts need them.
+        let parse = || {
+            let mut reader = BufReader::new(open(path)?);
+            let mut scan = Scan::default();
+            let mut line = String::new();
+
+            loop {
+                context.cancelled()?;
+                line.clear();
+                match reader.read_line(&mut line) {
+                    Ok(0) => break,
+                    Ok(_) => scan.read(&line)?,
+                    Err(error) => return Err(Error::Parse(path.to_path_buf(), error.to_string())),
                 }
+            }
+
+            let ids = Ids::File {
+                path: path.to_path_buf(),
+                offset: scan.ids(),
+            };
+            Ok(Inventory::new(scan.ranges, ids))
+        };
 
-                (ranges, ids)
-            });
+        Some(context.span("Parsing", parse))
+    }
 
-        Ok(input)
+    fn part1(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        input.ids().try_fold(0, |count, id| {
+            context.cancelled()?;
+            let fresh = input.index.contains(id?);
+            Ok(count + fresh as usize)
+        })
     }
 
-    fn part1(&self, (ranges, ids): &Self::Input) -> Result<Self::Answer1, Error> {
-        let count = ids
+    fn part2(
+        &self,
+        input: &Self::Input,
+        _shared: &(),
+        _context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        input.index.ranges.iter().try_fold(0_usize, |total, range| {
+            let count = usize::try_from(range.end() - range.start())
+                .ok()
+                .and_then(|count| count.checked_add(1))
+                .and_then(|count| total.checked_add(count))
+                .context("Count of fresh IDs overflowed usize")?;
+
+            Ok(count)
+        })
+    }
+
+    fn reference_part1(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        reference::part1(input, context)
+    }
+
+    fn reference_part2(
+        &self,
+        input: &Self::Input,
+        context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        reference::part2(input, context)
+    }
+
+    fn inspect(&self, input: &Self::Input) -> Vec<(&'static str, String)> {
+        let ids = input.ids().filter_map(Result::ok).collect::<Vec<_>>();
+        let range_ids = input
+            .ranges
             .iter()
-            .filter(|id| ranges.iter().any(|range| range.contains(id)))
-            .count();
+            .flat_map(|range| [*range.start(), *range.end()]);
+
+        vec![
+            ("Ranges", input.ranges.len().to_string()),
+            (
+                "Overlapping pairs",
+                overlapping_pairs(&input.ranges).to_string(),
+            ),
+            ("Ranges after merging", input.index.ranges.len().to_string()),
+            ("Range IDs", extent(range_ids)),
+            ("Available IDs", ids.len().to_string()),
+            ("Available ID values", extent(ids)),
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Result<String, Error> {
+        // Ranges spread over a space only somewhat larger than their total width, so some
+        // overlap and some IDs fall between them.
+        let space = (size as u64).saturating_mul(1_000).max(1);
+        let ranges = (0..size).map(|_| {
+            let start = rng.range(0..=space);
+            let end = start + rng.range(0..=2_000);
+            format!("{start}-{end}")
+        });
+        let ranges = ranges.collect::<Vec<_>>().join("\n");
+
+        let ids = (0..size).map(|_| rng.range(0..=space).to_string());
+        let ids = ids.collect::<Vec<_>>().join("\n");
+
+        Ok(format!("{ranges}\n\n{ids}"))
+    }
+}
+
+/// What a line of the input holds.
+enum Line<'a> {
+    /// A blank line or a comment.
+    Blank,
+
+    /// A range of fresh ingredient IDs, as the text either side of the `-`.
+    Range(&'a str, &'a str),
+
+    /// An available ingredient ID.
+    Id(&'a str),
+}
 
-        Ok(count)
+impl<'a> Line<'a> {
+    /// Any line with a `-` in it is a range, wherever it is in the input.
+    fn classify(line: &'a str) -> Self {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Self::Blank;
+        }
+
+        match line.split_once('-') {
+            Some((first, second)) => Self::Range(first, second),
+            None => Self::Id(line),
+        }
     }
+}
+
+/// The ranges read so far, along with where in the input the first ID was.
+#[derive(Default)]
+struct Scan {
+    ranges: Vec<RangeInclusive<u64>>,
+    first_id: Option<u64>,
+    offset: u64,
+}
+
+impl Scan {
+    /// Reads the next `line` of the input, keeping any range and checking any ID.
+    fn read(&mut self, line: &str) -> Result<(), Error> {
+        match Line::classify(line) {
+            Line::Blank => {}
+            Line::Range(first, second) => {
+                let (start, end) = (parse_id(first)?, parse_id(second)?);
+                if start > end {
+                    return Err(anyhow!("Range '{}' ends before it starts", line.trim()).into());
+                }
+
+                self.ranges.push(start..=end);
+            }
+            Line::Id(id) => {
+                parse_id(id)?;
+                self.first_id.get_or_insert(self.offset);
+            }
+        }
+
+        self.offset += line.len() as u64;
+        Ok(())
+    }
+
+    /// Where the IDs start, or the end of the input when there are none.
+    fn ids(&self) -> u64 {
+        self.first_id.unwrap_or(self.offset)
+    }
+}
+
+/// Where the available ingredient IDs are read from.
+enum Ids {
+    /// The input file, from `offset` bytes in, read again each time the IDs are needed.
+    File { path: PathBuf, offset: u64 },
+
+    /// Input given as text rather than as a file, from the first ID on.
+    Text(String),
+}
+
+/// The fresh ingredient ID ranges, along with where to read the available IDs from.
+pub struct Inventory {
+    index: RangeIndex,
+
+    /// The ranges as given, before merging, for the reference solution to check against.
+    ranges: Vec<RangeInclusive<u64>>,
 
-    fn part2(&self, (ranges, _): &Self::Input) -> Result<Self::Answer2, Error> {
-        let ranges = {
-            let mut r = ranges.clone();
-            r.sort_by(|a, b| a.start().cmp(b.start()));
-            r
+    ids: Ids,
+}
+
+impl Inventory {
+    fn new(ranges: Vec<RangeInclusive<u64>>, ids: Ids) -> Self {
+        Self {
+            index: RangeIndex::new(ranges.clone()),
+            ranges,
+            ids,
+        }
+    }
+
+    /// Reads the available ingredient IDs one line at a time, skipping the ranges, blank lines
+    /// and comments among them.
+    pub fn ids(&self) -> Box<dyn Iterator<Item = Result<u64, Error>> + '_> {
+        let ids = |line: &str| match Line::classify(line) {
+            Line::Id(id) => Some(parse_id(id)),
+            Line::Blank | Line::Range(..) => None,
         };
 
-        let result = ranges
-            .into_iter()
-            .fold(vec![], |mut acc, range| {
-                let Some(current) = acc.last_mut() else {
+        match &self.ids {
+            Ids::Text(text) => Box::new(text.split('\n').filter_map(ids)),
+            Ids::File { path, offset } => {
+                let lines = open(path).and_then(|mut file| {
+                    file.seek(SeekFrom::Start(*offset))
+                        .map_err(|error| Error::Parse(path.clone(), error.to_string()))?;
+                    Ok(BufReader::new(file).lines())
+                });
+
+                match lines {
+                    Ok(lines) => Box::new(lines.filter_map(move |line| match line {
+                        Ok(line) => ids(&line),
+                        Err(error) => Some(Err(Error::Parse(path.clone(), error.to_string()))),
+                    })),
+                    Err(error) => Box::new(iter::once(Err(error))),
+                }
+            }
+        }
+    }
+}
+
+impl Debug for Inventory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Inventory")
+            .field("index", &self.index)
+            .field("ids", &self.ids().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Serialize for Inventory {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ids = self
+            .ids()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(serde::ser::Error::custom)?;
+
+        let mut inventory = serializer.serialize_struct("Inventory", 2)?;
+        inventory.serialize_field("ranges", &self.ranges)?;
+        inventory.serialize_field("ids", &ids)?;
+        inventory.end()
+    }
+}
+
+/// Sorted, non-overlapping ID ranges which can be searched in logarithmic time.
+#[derive(Debug)]
+pub struct RangeIndex {
+    ranges: Vec<RangeInclusive<u64>>,
+}
+
+impl RangeIndex {
+    pub fn new(mut ranges: Vec<RangeInclusive<u64>>) -> Self {
+        ranges.sort_by(|a, b| a.start().cmp(b.start()));
+
+        let ranges = ranges.into_iter().fold(vec![], |mut acc, range| {
+            let Some(current) = acc.last_mut() else {
+                acc.push(range);
+                return acc;
+            };
+
+            let overlap = current.start() <= range.end() && range.start() <= current.end();
+
+            match overlap {
+                true => {
+                    let start = current.start().min(range.start());
+                    let end = current.end().max(range.end());
+                    *current = *start..=*end;
+                    acc
+                }
+                false => {
                     acc.push(range);
-                    return acc;
-                };
-
-                let overlap = current.start() <= range.end() && range.start() <= current.end();
-
-                match overlap {
-                    true => {
-                        let start = current.start().min(range.start());
-                        let end = current.end().max(range.end());
-                        *current = *start..=*end;
-                        acc
-                    }
-                    false => {
-                        acc.push(range);
-                        acc
-                    }
+                    acc
                 }
-            })
-            .into_iter()
-            .map(Iterator::count)
-            .sum();
+            }
+        });
+
+        Self { ranges }
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        let after = self.ranges.partition_point(|range| *range.start() <= id);
+
+        after > 0 && self.ranges[after - 1].contains(&id)
+    }
+}
+
+/// Counts the pairs of ranges sharing at least one ID.
+///
+/// With the ranges sorted by start, each overlaps exactly the later ones starting within it.
+fn overlapping_pairs(ranges: &[RangeInclusive<u64>]) -> usize {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|range| *range.start());
+
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(index, range)| {
+            sorted.partition_point(|other| other.start() <= range.end()) - index - 1
+        })
+        .sum()
+}
+
+fn open(path: &Path) -> Result<File, Error> {
+    File::open(path).map_err(|error| Error::Parse(path.to_path_buf(), error.to_string()))
+}
+
+fn parse_id(value: &str) -> Result<u64, Error> {
+    let id = value
+        .trim()
+        .parse::<u64>()
+        .with_context(|| format!("Invalid ingredient ID '{value}'"))?;
+
+    Ok(id)
+}
+
+/// Reports whether each ID given on the command line, or on stdin when none are, is fresh.
+pub fn query(config: &QueryConfig, input: &Inventory) -> Result<(), Error> {
+    let mut out = BufWriter::new(io::stdout().lock());
 
-        Ok(result)
+    let mut report = |id: u64| {
+        let status = match input.index.contains(id) {
+            true => "fresh".green(),
+            false => "spoiled".red(),
+        };
+
+        writeln!(out, "{id}: {status}").context("Failed to write query result")
+    };
+
+    match config.ids.is_empty() {
+        false => config.ids.iter().try_for_each(|id| report(*id))?,
+        true => {
+            for line in io::stdin().lock().lines() {
+                let line = line.context("Failed to read IDs from stdin")?;
+                if !line.trim().is_empty() {
+                    report(parse_id(&line)?)?;
+                }
+            }
+        }
     }
+
+    out.flush().context("Failed to write query results")?;
+
+    Ok(())
 }
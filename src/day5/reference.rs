@@ -0,0 +1,25 @@
+use std::collections::HashSet;
+
+use super::Inventory;
+use crate::{context::SolveContext, error::Error};
+
+/// Counts the available IDs which fall in any of the ranges as given, checking each range in turn.
+pub fn part1(input: &Inventory, context: &SolveContext) -> Result<usize, Error> {
+    input.ids().try_fold(0, |count, id| {
+        context.cancelled()?;
+        let id = id?;
+        let fresh = input.ranges.iter().any(|range| range.contains(&id));
+        Ok(count + fresh as usize)
+    })
+}
+
+/// Counts the fresh IDs by collecting every ID of every range into a set.
+pub fn part2(input: &Inventory, context: &SolveContext) -> Result<usize, Error> {
+    let mut fresh = HashSet::new();
+    for range in &input.ranges {
+        context.cancelled()?;
+        fresh.extend(range.clone());
+    }
+
+    Ok(fresh.len())
+}
@@ -14,6 +14,26 @@ pub enum Error {
     )]
     Parse(PathBuf, String),
 
+    #[error("{}", "Cancelled before finishing".yellow())]
+    Cancelled,
+
+    #[error(
+        "The input breaks an assumption of the solution:\n   ->  {}\n   ->  {}",
+        .0.bold(),
+        .1,
+    )]
+    Assumption(&'static str, String),
+
+    #[error(
+        "Invalid configuration in '{}':\n   ->  {}",
+        .0.display().to_string(),
+        .1.bold(),
+    )]
+    Config(PathBuf, String),
+
+    #[error("Invalid parameter:\n   ->  {}", .0.bold())]
+    Param(String),
+
     #[error(
         "Failed to complete problem:\n   ->  {}",
         .0.to_string().bold()
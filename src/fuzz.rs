@@ -0,0 +1,101 @@
+use crate::rng::Rng;
+
+/// Characters the inputs are made of, so flipped bytes are likely to still look almost valid.
+const ALPHABET: &[u8] = b"0123456789LR@.-,\n #";
+
+/// Characters outside ASCII, including digits `char::to_digit` doesn't accept and a zero width
+/// space that looks like nothing.
+const NON_ASCII: &[&str] = &["é", "🎄", "٣", "７", "\u{200b}", "\u{0}"];
+
+/// Applies a few random mutations to `content`, always leaving valid UTF-8 for `Problem::parse`.
+pub fn mutate(rng: &mut Rng, content: &str) -> String {
+    let mutations = rng.range(1..=4);
+
+    (0..mutations).fold(content.to_string(), |content, _| match rng.range(0..=6) {
+        0 => flip_byte(rng, content),
+        1 => truncate(rng, content),
+        2 => duplicate_line(rng, content),
+        3 => delete_line(rng, content),
+        4 => insert_non_ascii(rng, content),
+        5 => lengthen_number(rng, content),
+        _ => insert_blank_line(rng, content),
+    })
+}
+
+/// A byte offset into `content`, which may be `content.len()`.
+fn offset(rng: &mut Rng, content: &str) -> usize {
+    rng.range(0..=content.len() as u64) as usize
+}
+
+fn flip_byte(rng: &mut Rng, content: String) -> String {
+    let mut bytes = content.into_bytes();
+    if !bytes.is_empty() {
+        let index = rng.range(0..=bytes.len() as u64 - 1) as usize;
+        bytes[index] = ALPHABET[rng.range(0..=ALPHABET.len() as u64 - 1) as usize];
+    }
+
+    // Overwriting part of a multi-byte character leaves invalid UTF-8, which is replaced.
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn truncate(rng: &mut Rng, content: String) -> String {
+    let bytes = &content.as_bytes()[..offset(rng, &content)];
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn duplicate_line(rng: &mut Rng, content: String) -> String {
+    let mut lines = content.lines().collect::<Vec<_>>();
+    if let Some(last) = lines.len().checked_sub(1) {
+        let index = rng.range(0..=last as u64) as usize;
+        lines.insert(index, lines[index]);
+    }
+
+    lines.join("\n")
+}
+
+fn delete_line(rng: &mut Rng, content: String) -> String {
+    let mut lines = content.lines().collect::<Vec<_>>();
+    if let Some(last) = lines.len().checked_sub(1) {
+        lines.remove(rng.range(0..=last as u64) as usize);
+    }
+
+    lines.join("\n")
+}
+
+fn insert_non_ascii(rng: &mut Rng, content: String) -> String {
+    let character = NON_ASCII[rng.range(0..=NON_ASCII.len() as u64 - 1) as usize];
+    insert(rng, content, character)
+}
+
+/// Appends digits to a number, to push it past the width of whatever integer it's parsed into.
+fn lengthen_number(rng: &mut Rng, content: String) -> String {
+    let digits = content
+        .char_indices()
+        .filter(|(_, character)| character.is_ascii_digit())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let Some(last) = digits.len().checked_sub(1) else {
+        return content;
+    };
+
+    let index = digits[rng.range(0..=last as u64) as usize];
+    let mut content = content;
+    content.insert_str(index, &"9".repeat(rng.range(1..=30) as usize));
+    content
+}
+
+fn insert_blank_line(rng: &mut Rng, content: String) -> String {
+    insert(rng, content, "\n\n")
+}
+
+/// Inserts `text` at a random character boundary.
+fn insert(rng: &mut Rng, mut content: String, text: &str) -> String {
+    let mut index = offset(rng, &content);
+    while !content.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    content.insert_str(index, text);
+    content
+}
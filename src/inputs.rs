@@ -0,0 +1,101 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use serde::Deserialize;
+
+use crate::error::Error;
+
+/// Where inputs are stored when no root is given, `$XDG_DATA_HOME/aoc/inputs`.
+pub fn default_root() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_default()
+        .join("aoc")
+        .join("inputs")
+}
+
+/// The input for `day` under `root`, at `<root>/<profile>/dayN.txt`, or `<root>/dayN.txt` when
+/// there's no profile.
+pub fn path(root: &Path, profile: Option<&str>, day: u8) -> PathBuf {
+    let file = format!("day{day}.txt");
+
+    match profile {
+        Some(profile) => root.join(profile).join(file),
+        None => root.join(file),
+    }
+}
+
+/// Checks a profile names a single directory under the root, so it can't reach outside it.
+pub fn parse_profile(value: &str) -> Result<String, String> {
+    let valid = !value.is_empty() && value != "." && value != ".." && !value.contains(['/', '\\']);
+
+    match valid {
+        true => Ok(value.to_string()),
+        false => Err(format!("'{value}' isn't a profile name")),
+    }
+}
+
+/// The profiles under `root` holding an input for `day`, in alphabetical order.
+pub fn profiles(root: &Path, day: u8) -> Result<Vec<String>, Error> {
+    let entries = fs::read_dir(root)
+        .map_err(|error| anyhow!("Failed to list profiles in '{}': {error}", root.display()))?;
+
+    let mut profiles = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join(format!("day{day}.txt")).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    profiles.sort();
+
+    Ok(profiles)
+}
+
+/// The answers recorded for a profile's input in `<root>/<profile>/dayN.answers.toml`, with
+/// either part left out until it's known.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Answers {
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+}
+
+impl Answers {
+    /// Reads the answers recorded for `day` in `profile`, with none known when there's no file.
+    pub fn load(root: &Path, profile: &str, day: u8) -> Result<Self, Error> {
+        let path = root.join(profile).join(format!("day{day}.answers.toml"));
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => {
+                return Err(anyhow!("Failed to read '{}': {error}", path.display()).into());
+            }
+        };
+
+        let answers = toml::from_str(&content).map_err(|error| {
+            let message = error.message();
+            anyhow!("Invalid answers in '{}': {message}", path.display())
+        })?;
+
+        Ok(answers)
+    }
+}
+
+/// A recorded answer, written as a number or, when too large for TOML's integers, a string.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
@@ -1,19 +1,34 @@
-use clap::Parser;
-use cli::{Cli, Command};
+use std::{fs, io, process::ExitCode};
+
+use anyhow::Context;
+use clap::CommandFactory;
+use cli::{Cli, ColorMode, Command, ConfigCommand, ListConfig, ManConfig};
 use colored::Colorize;
+use config::Settings;
 use day1::Day1;
 use day2::Day2;
 use day3::Day3;
 use day4::Day4;
 use day5::Day5;
 use error::Error;
+use params::ParamSpec;
+use problem::Problem;
 use runner::Runner;
 
 mod cli;
+mod completions;
+mod config;
+mod context;
 mod error;
+mod fuzz;
+mod inputs;
 mod metrics;
+mod params;
 mod problem;
+mod progress;
+mod rng;
 mod runner;
+mod shrink;
 
 mod day1;
 mod day2;
@@ -21,8 +36,28 @@ mod day3;
 mod day4;
 mod day5;
 
+/// The implemented days, along with the parameters each accepts.
+const DAYS: [(u8, &[ParamSpec]); 5] = [
+    (1, Day1::PARAMS),
+    (2, Day2::PARAMS),
+    (3, Day3::PARAMS),
+    (4, Day4::PARAMS),
+    (5, Day5::PARAMS),
+];
+
 fn run() -> Result<(), Error> {
-    match Cli::parse().command {
+    completions::complete_if_asked();
+
+    let settings = Settings::load()?;
+    let cli = settings.parse()?;
+
+    match cli.color {
+        ColorMode::Auto => {}
+        ColorMode::Always => colored::control::set_override(true),
+        ColorMode::Never => colored::control::set_override(false),
+    }
+
+    match cli.command {
         Command::Run(config) => match config.day {
             1 => Runner::new(&config).run::<Day1>(),
             2 => Runner::new(&config).run::<Day2>(),
@@ -31,13 +66,172 @@ fn run() -> Result<(), Error> {
             5 => Runner::new(&config).run::<Day5>(),
             _ => Err(Error::Unimplemented),
         },
+        Command::Bench(config) => match config.run.day {
+            1 => Runner::new(&config.run).bench::<Day1>(&config.size, config.seed),
+            2 => Runner::new(&config.run).bench::<Day2>(&config.size, config.seed),
+            3 => Runner::new(&config.run).bench::<Day3>(&config.size, config.seed),
+            4 => Runner::new(&config.run).bench::<Day4>(&config.size, config.seed),
+            5 => Runner::new(&config.run).bench::<Day5>(&config.size, config.seed),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Gen(config) => {
+            let (size, seed, output) = (config.size, config.seed, config.output.as_deref());
+            match config.run.day {
+                1 => Runner::new(&config.run).generate::<Day1>(size, seed, output),
+                2 => Runner::new(&config.run).generate::<Day2>(size, seed, output),
+                3 => Runner::new(&config.run).generate::<Day3>(size, seed, output),
+                4 => Runner::new(&config.run).generate::<Day4>(size, seed, output),
+                5 => Runner::new(&config.run).generate::<Day5>(size, seed, output),
+                _ => Err(Error::Unimplemented),
+            }
+        }
+        Command::DiffTest(config) => {
+            let (cases, size, seed) = (config.cases, config.size, config.seed);
+            let shrink_to = config.shrink_to.as_deref();
+            match config.run.day {
+                1 => Runner::new(&config.run).diff_test::<Day1>(cases, size, seed, shrink_to),
+                2 => Runner::new(&config.run).diff_test::<Day2>(cases, size, seed, shrink_to),
+                3 => Runner::new(&config.run).diff_test::<Day3>(cases, size, seed, shrink_to),
+                4 => Runner::new(&config.run).diff_test::<Day4>(cases, size, seed, shrink_to),
+                5 => Runner::new(&config.run).diff_test::<Day5>(cases, size, seed, shrink_to),
+                _ => Err(Error::Unimplemented),
+            }
+        }
+        Command::Shrink(config) => match config.run.day {
+            1 => Runner::new(&config.run).shrink::<Day1>(&config.output),
+            2 => Runner::new(&config.run).shrink::<Day2>(&config.output),
+            3 => Runner::new(&config.run).shrink::<Day3>(&config.output),
+            4 => Runner::new(&config.run).shrink::<Day4>(&config.output),
+            5 => Runner::new(&config.run).shrink::<Day5>(&config.output),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Fuzz(config) => {
+            let (iterations, seed, output) = (config.iterations, config.seed, &config.output);
+            match config.run.day {
+                1 => Runner::new(&config.run).fuzz::<Day1>(iterations, seed, output),
+                2 => Runner::new(&config.run).fuzz::<Day2>(iterations, seed, output),
+                3 => Runner::new(&config.run).fuzz::<Day3>(iterations, seed, output),
+                4 => Runner::new(&config.run).fuzz::<Day4>(iterations, seed, output),
+                5 => Runner::new(&config.run).fuzz::<Day5>(iterations, seed, output),
+                _ => Err(Error::Unimplemented),
+            }
+        }
+        Command::Validate(config) => match config.run.day {
+            1 => Runner::new(&config.run).validate::<Day1>(),
+            2 => Runner::new(&config.run).validate::<Day2>(),
+            3 => Runner::new(&config.run).validate::<Day3>(),
+            4 => Runner::new(&config.run).validate::<Day4>(),
+            5 => Runner::new(&config.run).validate::<Day5>(),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::CheckInput(config) => match config.run.day {
+            1 => Runner::new(&config.run).check_input::<Day1>(),
+            2 => Runner::new(&config.run).check_input::<Day2>(),
+            3 => Runner::new(&config.run).check_input::<Day3>(),
+            4 => Runner::new(&config.run).check_input::<Day4>(),
+            5 => Runner::new(&config.run).check_input::<Day5>(),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Parse(config) => match config.run.day {
+            1 => Runner::new(&config.run).parse::<Day1>(),
+            2 => Runner::new(&config.run).parse::<Day2>(),
+            3 => Runner::new(&config.run).parse::<Day3>(),
+            4 => Runner::new(&config.run).parse::<Day4>(),
+            5 => Runner::new(&config.run).parse::<Day5>(),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Inspect(config) => match config.run.day {
+            1 => Runner::new(&config.run).inspect::<Day1>(),
+            2 => Runner::new(&config.run).inspect::<Day2>(),
+            3 => Runner::new(&config.run).inspect::<Day3>(),
+            4 => Runner::new(&config.run).inspect::<Day4>(),
+            5 => Runner::new(&config.run).inspect::<Day5>(),
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Simulate(config) => match config.run.day {
+            4 => {
+                let (_, input) = Runner::new(&config.run).load::<Day4>()?;
+                day4::automaton::simulate(&config, &input)
+            }
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Query(config) => match config.run.day {
+            5 => {
+                let (_, input) = Runner::new(&config.run).load::<Day5>()?;
+                day5::query(&config, &input)
+            }
+            _ => Err(Error::Unimplemented),
+        },
+        Command::Analyze(config) => match config.run.day {
+            1 => {
+                let (problem, input) = Runner::new(&config.run).load::<Day1>()?;
+                day1::analysis::analyze(&config, &problem, &input)
+            }
+            _ => Err(Error::Unimplemented),
+        },
+        Command::List(config) => {
+            list(&config);
+            Ok(())
+        }
+        Command::Config(ConfigCommand::Show) => {
+            settings.show();
+            Ok(())
+        }
+        Command::Completions(config) => completions::print_script(config.shell),
+        Command::Man(config) => man(&config),
     }
 }
 
-fn main() -> Result<(), Error> {
-    if let Err(error) = run() {
-        eprintln!("{}: {error}", "error".red());
+fn list(config: &ListConfig) {
+    for (day, params) in DAYS {
+        println!("🎄 {}", format!("Day {day}").bold());
+
+        if !config.params {
+            continue;
+        }
+
+        if params.is_empty() {
+            println!("   {}", "No parameters".dimmed());
+        }
+
+        for param in params {
+            println!(
+                "   {:<20} {:<10} {:<8} {}",
+                param.name.green(),
+                param.kind.to_string().yellow(),
+                format!("[{}]", param.default),
+                param.help,
+            );
+        }
+    }
+}
+
+/// Prints the man page, or writes a page for each command into the output directory.
+fn man(config: &ManConfig) -> Result<(), Error> {
+    let command = Cli::command();
+
+    match &config.output {
+        Some(directory) => {
+            fs::create_dir_all(directory)
+                .and_then(|_| clap_mangen::generate_to(command, directory))
+                .with_context(|| {
+                    format!("Failed to write man pages to '{}'", directory.display())
+                })?;
+        }
+        None => clap_mangen::Man::new(command)
+            .render(&mut io::stdout().lock())
+            .context("Failed to write the man page")?,
     }
 
     Ok(())
 }
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}: {error}", "error".red());
+            ExitCode::FAILURE
+        }
+    }
+}
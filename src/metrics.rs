@@ -1,75 +1,282 @@
-use std::time::{Duration, Instant};
+use std::{
+    ops::AddAssign,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
+use serde::Serialize;
+
+/// Timings of named, possibly nested and repeated, spans of a run.
 pub struct Metrics {
     pub enabled: bool,
-    pub total: Duration,
-    pub parsing: Option<Duration>,
-    pub part1: Option<Duration>,
-    pub part2: Option<Duration>,
+
+    /// Whether every span is kept individually, rather than only added up, to draw a timeline.
+    timeline: bool,
+
+    started: Instant,
+    spans: Mutex<SpanTree>,
 }
 
 impl Metrics {
     pub fn start(enabled: bool) -> Self {
         Self {
             enabled,
-            total: Duration::ZERO,
-            parsing: None,
-            part1: None,
-            part2: None,
+            timeline: false,
+            started: Instant::now(),
+            spans: Mutex::default(),
         }
     }
 
-    pub fn track_parsing<TFn, R>(&mut self, f: TFn) -> R
+    /// Also keeps each span as it happened, for `timeline`.
+    pub fn with_timeline(mut self, timeline: bool) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Times `f` as a span called `name`, nested under whichever span is currently running.
+    ///
+    /// Spans with the same name and parent are merged, adding up their calls and time.
+    pub fn span<TFn, R>(&self, name: &str, f: TFn) -> R
     where
         TFn: FnOnce() -> R,
     {
-        if !self.enabled {
+        if !self.enabled && !self.timeline {
             return f();
         }
 
+        let node = self.lock().enter(name);
+        let usage = Usage::now();
         let start = Instant::now();
         let result = f();
-        self.parsing = Some(start.elapsed());
+        let elapsed = start.elapsed();
+        let usage = Usage::now().since(usage);
+
+        let mut spans = self.lock();
+        spans.exit(node, elapsed, usage);
+        if self.timeline {
+            spans.events.push(Event {
+                node,
+                start: start - self.started,
+                elapsed,
+            });
+        }
+
         result
     }
 
-    pub fn track_part1<TFn, R>(&mut self, f: TFn) -> R
-    where
-        TFn: FnOnce() -> R,
-    {
-        if !self.enabled {
-            return f();
+    /// The real time since the metrics started, including anything that ran outside of a span.
+    pub fn wall(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The time spent in the outermost spans.
+    pub fn total(&self) -> Duration {
+        let spans = self.lock();
+        spans
+            .roots
+            .iter()
+            .map(|root| spans.nodes[*root].total)
+            .sum()
+    }
+
+    /// Every span, depth first, along with how deeply it's nested.
+    pub fn spans(&self) -> Vec<(usize, Span)> {
+        let spans = self.lock();
+        let mut flattened = vec![];
+        let mut stack = spans
+            .roots
+            .iter()
+            .rev()
+            .map(|root| (0, *root))
+            .collect::<Vec<_>>();
+
+        while let Some((depth, node)) = stack.pop() {
+            let span = &spans.nodes[node];
+            flattened.push((depth, span.clone()));
+            stack.extend(span.children.iter().rev().map(|child| (depth + 1, *child)));
         }
 
-        let start = Instant::now();
-        let result = f();
-        self.part1 = Some(start.elapsed());
-        result
+        flattened
     }
 
-    pub fn track_part2<TFn, R>(&mut self, f: TFn) -> R
-    where
-        TFn: FnOnce() -> R,
-    {
-        if !self.enabled {
-            return f();
+    /// Every span kept since `with_timeline`, in Chrome's trace event format.
+    pub fn timeline(&self) -> Timeline {
+        let spans = self.lock();
+        let events = spans.events.iter().map(|event| TraceEvent {
+            name: spans.nodes[event.node].name.clone(),
+            phase: "X",
+            timestamp: event.start.as_secs_f64() * 1e6,
+            duration: event.elapsed.as_secs_f64() * 1e6,
+            process: 1,
+            thread: 1,
+        });
+
+        Timeline {
+            trace_events: events.collect(),
+            display_time_unit: "ms",
         }
+    }
 
-        let start = Instant::now();
-        let result = f();
-        self.part2 = Some(start.elapsed());
-        result
+    fn lock(&self) -> std::sync::MutexGuard<'_, SpanTree> {
+        self.spans.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub name: String,
+    pub count: usize,
+    pub total: Duration,
+    pub usage: Usage,
+    children: Vec<usize>,
+}
+
+/// A single run of a span, relative to when the metrics started.
+struct Event {
+    node: usize,
+    start: Duration,
+    elapsed: Duration,
+}
+
+#[derive(Default)]
+struct SpanTree {
+    nodes: Vec<Span>,
+    roots: Vec<usize>,
+    events: Vec<Event>,
+
+    /// The spans currently running, innermost last.
+    running: Vec<usize>,
+}
+
+impl SpanTree {
+    fn enter(&mut self, name: &str) -> usize {
+        let parent = self.running.last().copied();
+        let siblings = match parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+
+        let existing = siblings
+            .iter()
+            .find(|sibling| self.nodes[**sibling].name == name)
+            .copied();
+
+        let node = existing.unwrap_or_else(|| {
+            let node = self.nodes.len();
+            self.nodes.push(Span {
+                name: name.to_string(),
+                count: 0,
+                total: Duration::ZERO,
+                usage: Usage::default(),
+                children: vec![],
+            });
+
+            match parent {
+                Some(parent) => self.nodes[parent].children.push(node),
+                None => self.roots.push(node),
+            }
+
+            node
+        });
+
+        self.running.push(node);
+        node
     }
 
-    pub fn finish(mut self) -> Self {
-        if !self.enabled {
-            return self;
+    fn exit(&mut self, node: usize, elapsed: Duration, usage: Usage) {
+        self.running.retain(|running| *running != node);
+
+        let span = &mut self.nodes[node];
+        span.count += 1;
+        span.total += elapsed;
+        span.usage += usage;
+    }
+}
+
+/// The resources the current thread has used, as reported by `getrusage`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    /// CPU time spent running the program's own code.
+    pub user: Duration,
+
+    /// CPU time the kernel spent on the program's behalf, such as reading files.
+    pub system: Duration,
+
+    /// Blocks read from storage rather than the page cache, so a file read with none was
+    /// already cached.
+    pub blocks_read: u64,
+}
+
+impl Usage {
+    fn now() -> Self {
+        #[cfg(target_os = "linux")]
+        let who = libc::RUSAGE_THREAD;
+        #[cfg(not(target_os = "linux"))]
+        let who = libc::RUSAGE_SELF;
+
+        // SAFETY: `getrusage` only writes to the struct it's given, which is plain data that
+        // zeroed memory is a valid value of.
+        let usage = unsafe {
+            let mut usage = std::mem::zeroed::<libc::rusage>();
+            match libc::getrusage(who, &mut usage) {
+                0 => usage,
+                _ => return Self::default(),
+            }
+        };
+
+        let duration = |time: libc::timeval| {
+            Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+        };
+
+        Self {
+            user: duration(usage.ru_utime),
+            system: duration(usage.ru_stime),
+            blocks_read: usage.ru_inblock as u64,
         }
+    }
 
-        self.total = self.parsing.unwrap_or_default()
-            + self.part1.unwrap_or_default()
-            + self.part2.unwrap_or_default();
+    fn since(self, earlier: Self) -> Self {
+        Self {
+            user: self.user.saturating_sub(earlier.user),
+            system: self.system.saturating_sub(earlier.system),
+            blocks_read: self.blocks_read.saturating_sub(earlier.blocks_read),
+        }
+    }
+}
 
-        self
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.user += other.user;
+        self.system += other.system;
+        self.blocks_read += other.blocks_read;
     }
 }
+
+/// A trace in Chrome's JSON object format.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timeline {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+}
+
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+
+    #[serde(rename = "ph")]
+    phase: &'static str,
+
+    /// Microseconds since the metrics started.
+    #[serde(rename = "ts")]
+    timestamp: f64,
+
+    #[serde(rename = "dur")]
+    duration: f64,
+
+    #[serde(rename = "pid")]
+    process: u32,
+
+    #[serde(rename = "tid")]
+    thread: usize,
+}
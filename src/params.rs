@@ -0,0 +1,289 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::error::Error;
+
+/// The type of value a parameter accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Bool,
+    UInt,
+
+    /// An unsigned integer, or `none` to leave it unset.
+    OptionalUInt,
+
+    /// A comma separated list of unsigned integers, which may be empty.
+    UIntList,
+}
+
+impl Display for ParamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            Self::Bool => "bool",
+            Self::UInt => "uint",
+            Self::OptionalUInt => "uint|none",
+            Self::UIntList => "uint,...",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Describes a value a problem can be tuned with through `--param name=value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: &'static str,
+    pub help: &'static str,
+
+    /// The smallest and largest unsigned integers accepted, any when `None`.
+    pub bounds: Option<(u64, u64)>,
+}
+
+impl ParamSpec {
+    /// Parses `value` into the declared type, checking any integers fall within the bounds.
+    pub fn parse(&self, value: &str) -> Result<ParamValue, String> {
+        let value = ParamValue::parse(self.kind, value)?;
+        let Some((min, max)) = self.bounds else {
+            return Ok(value);
+        };
+
+        let uints = match &value {
+            ParamValue::Bool(_) | ParamValue::OptionalUInt(None) => vec![],
+            ParamValue::UInt(value) | ParamValue::OptionalUInt(Some(value)) => vec![*value],
+            ParamValue::UIntList(values) => values.clone(),
+        };
+
+        match uints.iter().find(|value| !(min..=max).contains(*value)) {
+            Some(outside) => Err(format!("'{outside}' is not between {min} and {max}")),
+            None => Ok(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamValue {
+    Bool(bool),
+    UInt(u64),
+    OptionalUInt(Option<u64>),
+    UIntList(Vec<u64>),
+}
+
+impl ParamValue {
+    pub fn parse(kind: ParamKind, value: &str) -> Result<Self, String> {
+        let uint = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("'{value}' is not an unsigned integer"))
+        };
+
+        match kind {
+            ParamKind::Bool => match value.trim() {
+                "true" | "yes" | "1" => Ok(Self::Bool(true)),
+                "false" | "no" | "0" => Ok(Self::Bool(false)),
+                _ => Err(format!("'{value}' is not a boolean")),
+            },
+            ParamKind::UInt => uint(value).map(Self::UInt),
+            ParamKind::OptionalUInt => match value.trim() {
+                "" | "none" => Ok(Self::OptionalUInt(None)),
+                value => uint(value).map(|value| Self::OptionalUInt(Some(value))),
+            },
+            ParamKind::UIntList => value
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(uint)
+                .collect::<Result<_, _>>()
+                .map(Self::UIntList),
+        }
+    }
+}
+
+/// The validated parameter values for a problem, with defaults filled in.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<&'static str, ParamValue>,
+}
+
+impl Params {
+    /// Checks the `name=value` pairs given against `specs`, parsing each into its declared type.
+    pub fn resolve(specs: &[ParamSpec], given: &[(String, String)]) -> Result<Self, Error> {
+        let mut values = specs
+            .iter()
+            .map(|spec| {
+                let value = spec.parse(spec.default).map_err(|error| {
+                    Error::Param(format!("default for '{}': {error}", spec.name))
+                })?;
+
+                Ok((spec.name, value))
+            })
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+
+        for (name, value) in given {
+            let Some(spec) = specs.iter().find(|spec| spec.name == name) else {
+                let expected = match specs.is_empty() {
+                    true => "this day takes no parameters".to_string(),
+                    false => {
+                        let names = specs.iter().map(|spec| spec.name).collect::<Vec<_>>();
+                        format!("expected one of {}", names.join(", "))
+                    }
+                };
+
+                return Err(Error::Param(format!(
+                    "unknown parameter '{name}', {expected}"
+                )));
+            };
+
+            let value = spec
+                .parse(value)
+                .map_err(|error| Error::Param(format!("{name}: {error}")))?;
+
+            values.insert(spec.name, value);
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        match self.get(name) {
+            ParamValue::Bool(value) => *value,
+            value => panic!("Parameter '{name}' is {value:?}, not a bool"),
+        }
+    }
+
+    pub fn uint(&self, name: &str) -> u64 {
+        match self.get(name) {
+            ParamValue::UInt(value) => *value,
+            value => panic!("Parameter '{name}' is {value:?}, not a uint"),
+        }
+    }
+
+    pub fn optional_uint(&self, name: &str) -> Option<u64> {
+        match self.get(name) {
+            ParamValue::OptionalUInt(value) => *value,
+            value => panic!("Parameter '{name}' is {value:?}, not an optional uint"),
+        }
+    }
+
+    pub fn uint_list(&self, name: &str) -> &[u64] {
+        match self.get(name) {
+            ParamValue::UIntList(value) => value,
+            value => panic!("Parameter '{name}' is {value:?}, not a uint list"),
+        }
+    }
+
+    fn get(&self, name: &str) -> &ParamValue {
+        self.values
+            .get(name)
+            .unwrap_or_else(|| panic!("Parameter '{name}' was never declared"))
+    }
+}
+
+/// Splits a `--param` argument into its name and value.
+pub fn parse_assignment(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(kind: ParamKind, bounds: Option<(u64, u64)>) -> ParamSpec {
+        ParamSpec {
+            name: "test",
+            kind,
+            default: "",
+            help: "",
+            bounds,
+        }
+    }
+
+    #[test]
+    fn accepts_values_within_the_bounds() {
+        let spec = spec(ParamKind::UInt, Some((1, 10)));
+
+        assert_eq!(spec.parse("1"), Ok(ParamValue::UInt(1)));
+        assert_eq!(spec.parse("10"), Ok(ParamValue::UInt(10)));
+    }
+
+    #[test]
+    fn rejects_values_outside_the_bounds() {
+        let spec = spec(ParamKind::UInt, Some((1, 10)));
+
+        assert_eq!(
+            spec.parse("0"),
+            Err("'0' is not between 1 and 10".to_string())
+        );
+        assert_eq!(
+            spec.parse("11"),
+            Err("'11' is not between 1 and 10".to_string())
+        );
+    }
+
+    #[test]
+    fn accepts_any_value_without_bounds() {
+        let spec = spec(ParamKind::UInt, None);
+
+        assert_eq!(spec.parse("0"), Ok(ParamValue::UInt(0)));
+        assert_eq!(
+            spec.parse(&u64::MAX.to_string()),
+            Ok(ParamValue::UInt(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn bounds_an_optional_value_only_when_set() {
+        let spec = spec(ParamKind::OptionalUInt, Some((2, 5)));
+
+        assert_eq!(spec.parse("none"), Ok(ParamValue::OptionalUInt(None)));
+        assert_eq!(spec.parse("3"), Ok(ParamValue::OptionalUInt(Some(3))));
+        assert!(spec.parse("6").is_err());
+    }
+
+    #[test]
+    fn bounds_every_value_of_a_list() {
+        let spec = spec(ParamKind::UIntList, Some((1, 5)));
+
+        assert_eq!(spec.parse(""), Ok(ParamValue::UIntList(vec![])));
+        assert_eq!(spec.parse("1,5"), Ok(ParamValue::UIntList(vec![1, 5])));
+        assert_eq!(
+            spec.parse("1,6,0"),
+            Err("'6' is not between 1 and 5".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_kind_before_the_bounds() {
+        let spec = spec(ParamKind::UInt, Some((1, 10)));
+
+        assert_eq!(
+            spec.parse("-1"),
+            Err("'-1' is not an unsigned integer".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_defaults_and_rejects_unknown_names() {
+        let specs = [ParamSpec {
+            default: "3",
+            ..spec(ParamKind::UInt, Some((1, 10)))
+        }];
+
+        let params = Params::resolve(&specs, &[]).unwrap();
+        assert_eq!(params.uint("test"), 3);
+
+        let given = [("test".to_string(), "11".to_string())];
+        assert!(matches!(
+            Params::resolve(&specs, &given),
+            Err(Error::Param(_))
+        ));
+
+        let given = [("other".to_string(), "1".to_string())];
+        assert!(matches!(
+            Params::resolve(&specs, &given),
+            Err(Error::Param(_))
+        ));
+    }
+}
@@ -4,17 +4,27 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{cli::RunConfig, error::Error};
+use crate::{cli::Part, context::SolveContext, error::Error, params::ParamSpec, rng::Rng};
 use anyhow::Context;
+use serde::Serialize;
 
-pub trait Problem<'a> {
-    type Input: Debug;
+pub trait Problem {
+    type Input: Debug + Serialize;
     type Answer1: Display;
     type Answer2: Display;
 
-    fn init(config: &'a RunConfig) -> Self
+    /// Work both parts build on, computed once after parsing rather than by each part, so part 2
+    /// can carry on from where part 1 got to. Problems with nothing to share use `()`.
+    type Shared: Default;
+
+    /// The parameters the problem can be tuned with, resolved before `init`.
+    const PARAMS: &'static [ParamSpec] = &[];
+
+    /// Sets the problem up from its resolved parameters, failing with `Error::Param` when they
+    /// contradict each other.
+    fn init(context: &SolveContext) -> Result<Self, Error>
     where
-        Self: Sized + 'a;
+        Self: Sized;
 
     fn path(&self) -> Result<PathBuf, Error> {
         let type_path = type_name_of_val(self);
@@ -26,15 +36,123 @@ pub trait Problem<'a> {
         Ok(format!("./src/{day}/input.txt").into())
     }
 
-    fn parse(&self, _content: &str, _path: &Path) -> Result<Self::Input, Error> {
+    fn parse(
+        &self,
+        _content: &str,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Result<Self::Input, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Parses the input straight from the file at `path`, for problems that read some of it as
+    /// they go rather than holding all of it, timing it as the `Parsing` span. Returning `None` has
+    /// the whole file read for `parse`.
+    fn parse_file(
+        &self,
+        _path: &Path,
+        _context: &SolveContext,
+    ) -> Option<Result<Self::Input, Error>> {
+        None
+    }
+
+    fn share(&self, _input: &Self::Input, _context: &SolveContext) -> Result<Self::Shared, Error> {
+        Ok(Self::Shared::default())
+    }
+
+    fn part1(
+        &self,
+        _input: &Self::Input,
+        _shared: &Self::Shared,
+        _context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
         Err(Error::Unimplemented)
     }
 
-    fn part1(&self, _input: &Self::Input) -> Result<Self::Answer1, Error> {
+    fn part2(
+        &self,
+        _input: &Self::Input,
+        _shared: &Self::Shared,
+        _context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
         Err(Error::Unimplemented)
     }
 
-    fn part2(&self, _input: &Self::Input) -> Result<Self::Answer2, Error> {
+    /// A straightforward, slower solution to part 1 to compare the optimised one against.
+    fn reference_part1(
+        &self,
+        _input: &Self::Input,
+        _context: &SolveContext,
+    ) -> Result<Self::Answer1, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// A straightforward, slower solution to part 2 to compare the optimised one against.
+    fn reference_part2(
+        &self,
+        _input: &Self::Input,
+        _context: &SolveContext,
+    ) -> Result<Self::Answer2, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// What the solutions take for granted about the input, checked before solving so an input
+    /// breaking them fails clearly rather than with a wrong answer or a panic.
+    fn assumptions(&self, _input: &Self::Input) -> Vec<Assumption> {
+        vec![]
+    }
+
+    /// Statistics about the input worth knowing before writing a solution, as labelled values.
+    fn inspect(&self, _input: &Self::Input) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+
+    /// Generates a random input roughly `size` units large, for benchmarking and stress tests.
+    fn generate(&self, _rng: &mut Rng, _size: usize) -> Result<String, Error> {
         Err(Error::Unimplemented)
     }
 }
+
+/// Something the solutions rely on about the input without checking it while solving.
+pub struct Assumption {
+    pub description: &'static str,
+
+    /// Where the input breaks the assumption, or `None` when it holds.
+    pub violation: Option<String>,
+
+    /// The part relying on the assumption, or `Both`.
+    pub part: Part,
+}
+
+impl Assumption {
+    pub fn new(description: &'static str, violation: Option<String>) -> Self {
+        Self {
+            description,
+            violation,
+            part: Part::Both,
+        }
+    }
+
+    /// Marks the assumption as one only `part` relies on.
+    pub fn for_part(self, part: Part) -> Self {
+        Self { part, ..self }
+    }
+
+    /// Whether solving `part` relies on the assumption.
+    pub fn applies_to(&self, part: &Part) -> bool {
+        self.part == Part::Both || *part == Part::Both || self.part == *part
+    }
+}
+
+/// Describes the smallest and largest of `values`, for `Problem::inspect`.
+pub fn extent<T>(values: impl IntoIterator<Item = T>) -> String
+where
+    T: Ord + Copy + Display,
+{
+    let values = values.into_iter().collect::<Vec<_>>();
+
+    match (values.iter().min(), values.iter().max()) {
+        (Some(min), Some(max)) => format!("{min} to {max}"),
+        _ => "none".to_string(),
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use humanize_duration::{Truncate, prelude::DurationExt};
+
+/// How often the bar is redrawn.
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// How long a part runs before the bar appears, so quick parts don't flicker one up.
+const DELAY: Duration = Duration::from_millis(250);
+
+const BAR_WIDTH: usize = 30;
+
+/// How far through its work a long running part is, in units the part picks.
+#[derive(Default)]
+pub struct Progress {
+    current: AtomicU64,
+    total: AtomicU64,
+    started: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    /// Starts counting towards `total` units of work.
+    pub fn start(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+        self.current.store(0, Ordering::Relaxed);
+        let mut started = self
+            .started
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        *started = Some(Instant::now());
+    }
+
+    pub fn advance(&self, units: u64) {
+        self.current.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// Forgets any earlier work, so the next part starts with no progress shown.
+    fn reset(&self) {
+        self.start(0);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let started = self
+            .started
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let elapsed = started.map(|started| started.elapsed()).unwrap_or_default();
+
+        Snapshot {
+            current: self.current.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+            elapsed,
+        }
+    }
+}
+
+struct Snapshot {
+    current: u64,
+    total: u64,
+    elapsed: Duration,
+}
+
+impl Snapshot {
+    /// The bar along with the rate and ETA, or nothing when no work has been started.
+    fn render(&self, label: &str) -> Option<String> {
+        if self.total == 0 {
+            return None;
+        }
+
+        let current = self.current.min(self.total);
+        let fraction = current as f64 / self.total as f64;
+        let filled = (fraction * BAR_WIDTH as f64) as usize;
+        let bar = format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled));
+
+        let rate = current as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON);
+        let eta = match rate > 0.0 {
+            true => {
+                let remaining = (self.total - current) as f64 / rate;
+                Duration::from_secs_f64(remaining.ceil())
+                    .human(Truncate::Second)
+                    .to_string()
+            }
+            false => "?".to_string(),
+        };
+
+        Some(format!(
+            "{label} {bar} {:>3}% {current}/{} {rate:.0}/s ETA {}",
+            (fraction * 100.0) as u64,
+            self.total,
+            match eta.is_empty() {
+                true => "0s",
+                false => &eta,
+            },
+        ))
+    }
+}
+
+/// Runs `f` while drawing a bar on stderr for the work it reports through `progress`.
+pub fn show_while<TFn, R>(label: &str, progress: &Progress, enabled: bool, f: TFn) -> R
+where
+    TFn: FnOnce() -> R,
+{
+    progress.reset();
+
+    if !enabled {
+        return f();
+    }
+
+    let done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let renderer = scope.spawn(|| {
+            let opened = Instant::now();
+            let mut drawn = false;
+
+            while !done.load(Ordering::Relaxed) {
+                thread::park_timeout(REFRESH);
+                if done.load(Ordering::Relaxed) || opened.elapsed() < DELAY {
+                    continue;
+                }
+
+                if let Some(line) = progress.snapshot().render(label) {
+                    eprint!("\r\x1b[2K{line}");
+                    drawn = true;
+                }
+            }
+
+            if drawn {
+                eprint!("\r\x1b[2K");
+            }
+        });
+
+        let result = f();
+        done.store(true, Ordering::Relaxed);
+        renderer.thread().unpark();
+
+        result
+    })
+}
@@ -1,17 +1,38 @@
-use std::fs;
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    io::{self, IsTerminal, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
+use anyhow::{Context, anyhow};
 use colored::Colorize;
 use humanize_duration::{Truncate, prelude::DurationExt};
+use serde_json::{Value, json};
 
 use crate::{
-    cli::{Part, RunConfig},
+    cli::{Format, Part, RunConfig},
+    context::SolveContext,
     error::{Error, ResultExt},
+    fuzz,
+    inputs::{self, Answer, Answers},
     metrics::Metrics,
+    params::Params,
     problem::Problem,
+    progress,
+    rng::Rng,
+    shrink,
 };
 
 const PADDING: &str = "   ";
 
+/// The span reading the input is timed under, which is reported as I/O.
+const READING: &str = "Reading";
+
 pub struct Runner<'a> {
     config: &'a RunConfig,
 }
@@ -23,87 +44,1173 @@ impl<'a> Runner<'a> {
 
     pub fn run<T>(&self) -> Result<(), Error>
     where
-        T: Problem<'a> + 'a,
+        T: Problem,
+    {
+        let json = self.config.format == Format::Json;
+        if json {
+            colored::control::set_override(false);
+        }
+
+        let timeline = self.config.trace_timeline.is_some();
+        let metrics = Arc::new(Metrics::start(self.config.metrics).with_timeline(timeline));
+        let (problem, context) = self.init::<T>(Arc::clone(&metrics))?;
+
+        if !json {
+            let message = format!("Day {}", self.config.day).bold();
+            println!("🎄 {message}");
+        }
+
+        let input = self.read_input(&problem, &context)?;
+        self.check_assumptions(&problem, &input)?;
+
+        let shared = match self.config.reference {
+            true => T::Shared::default(),
+            false => context.span("Shared", || problem.share(&input, &context))?,
+        };
+
+        // The bar would garble JSON or a redirected stderr, so it's only drawn for people.
+        let show_progress = !json && io::stderr().is_terminal();
+
+        let answer1 =
+            match self.config.part {
+                Part::One | Part::Both => Some(Self::solve(
+                    "Part 1",
+                    &context,
+                    show_progress,
+                    || match self.config.reference {
+                        true => problem.reference_part1(&input, &context),
+                        false => problem.part1(&input, &shared, &context),
+                    },
+                )),
+                _ => None,
+            };
+
+        let answer2 =
+            match self.config.part {
+                Part::Two | Part::Both => Some(Self::solve(
+                    "Part 2",
+                    &context,
+                    show_progress,
+                    || match self.config.reference {
+                        true => problem.reference_part2(&input, &context),
+                        false => problem.part2(&input, &shared, &context),
+                    },
+                )),
+                _ => None,
+            };
+
+        match self.config.format {
+            Format::Text => {
+                if let Some(answer) = &answer1 {
+                    println!("{PADDING}Part 1:  {}", answer.format());
+                }
+
+                if let Some(answer) = &answer2 {
+                    println!("{PADDING}Part 2:  {}", answer.format());
+                }
+
+                if metrics.enabled {
+                    self.report_metrics(&metrics);
+                }
+            }
+            Format::Json => self.print_json(&answer1, &answer2, &metrics),
+        }
+
+        if let Some(path) = &self.config.trace_timeline {
+            Self::write_timeline(&metrics, path)?;
+        }
+
+        // Failed parts are reported alongside the answers, but still fail the run.
+        let failed = [
+            ("part 1", answer1.is_some_and(|answer| answer.is_err())),
+            ("part 2", answer2.is_some_and(|answer| answer.is_err())),
+        ]
+        .into_iter()
+        .filter_map(|(part, failed)| failed.then_some(part))
+        .collect::<Vec<_>>();
+
+        match failed.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow!("Solving {} failed", failed.join(" and ")).into()),
+        }
+    }
+
+    /// Times a part under `label`, showing a progress bar while it runs if `show_progress`.
+    fn solve<TFn, R>(label: &'static str, context: &SolveContext, show_progress: bool, f: TFn) -> R
+    where
+        TFn: FnOnce() -> R,
+    {
+        progress::show_while(label, context.progress(), show_progress, || {
+            context.span(label, f)
+        })
+    }
+
+    /// Validates the parameters given for the problem and initialises it with a context holding
+    /// them, which records spans into `metrics`.
+    fn init<T>(&self, metrics: Arc<Metrics>) -> Result<(T, SolveContext), Error>
+    where
+        T: Problem,
+    {
+        let params = Params::resolve(T::PARAMS, &self.config.params)?;
+        let context = SolveContext::new(params, self.config.verbose, metrics);
+
+        if let Some(timeout) = self.config.timeout {
+            context.cancel_after(timeout);
+        }
+
+        let problem = context.span("Init", || T::init(&context))?;
+
+        Ok((problem, context))
+    }
+
+    /// Initialises the problem and parses its input, for modes other than solving.
+    pub fn load<T>(&self) -> Result<(T, T::Input), Error>
+    where
+        T: Problem,
     {
-        let problem = T::init(self.config);
-        let message = format!("Day {}", self.config.day).bold();
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let input = self.read_input(&problem, &context)?;
+
+        Ok((problem, input))
+    }
+
+    /// Reports which of the solutions' assumptions the input holds to.
+    pub fn check_input<T>(&self) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let message = format!("Day {} input check", self.config.day).bold();
         println!("🎄 {message}");
 
-        let mut metrics = Metrics::start(self.config.metrics);
+        let input = self.read_input(&problem, &context)?;
+        let assumptions = problem
+            .assumptions(&input)
+            .into_iter()
+            .filter(|assumption| assumption.applies_to(&self.config.part))
+            .collect::<Vec<_>>();
+        if assumptions.is_empty() {
+            println!("{PADDING}{}", "No assumptions to check".dimmed());
+            return Ok(());
+        }
 
-        let input = self.read_input(&problem, &mut metrics)?;
+        for assumption in &assumptions {
+            match &assumption.violation {
+                None => println!("{PADDING}{}  {}", "holds".green(), assumption.description),
+                Some(violation) => {
+                    println!("{PADDING}{}  {}", "fails".red(), assumption.description);
+                    println!("{PADDING}{PADDING}   {}", violation.bold());
+                }
+            }
+        }
+
+        let broken = assumptions
+            .iter()
+            .filter(|assumption| assumption.violation.is_some())
+            .count();
 
-        let answer1 = match self.config.part {
-            Part::One | Part::Both => {
-                let answer = metrics.track_part1(|| problem.part1(&input)).format();
-                format!("{PADDING}Part 1:  {answer}\n")
+        match broken {
+            0 => Ok(()),
+            broken => {
+                Err(anyhow!("{broken} of {} assumptions don't hold", assumptions.len()).into())
             }
-            _ => "".into(),
-        };
+        }
+    }
+
+    /// Fails on the first assumption of the parts being solved that `input` breaks.
+    fn check_assumptions<T>(&self, problem: &T, input: &T::Input) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let broken = problem
+            .assumptions(input)
+            .into_iter()
+            .filter(|assumption| assumption.applies_to(&self.config.part))
+            .find_map(|assumption| {
+                let violation = assumption.violation?;
+                Some(Error::Assumption(assumption.description, violation))
+            });
+
+        match broken {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
 
-        let answer2 = match self.config.part {
-            Part::Two | Part::Both => {
-                let answer = metrics.track_part2(|| problem.part2(&input)).format();
-                format!("{PADDING}Part 2:  {answer}\n")
+    /// Prints the parsed input, as pretty `Debug` output or as JSON with `--format json`.
+    pub fn parse<T>(&self) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (_, input) = self.load::<T>()?;
+        let output = match self.config.format {
+            Format::Text => format!("{input:#?}"),
+            Format::Json => {
+                serde_json::to_string_pretty(&input).context("Failed to serialise the input")?
             }
-            _ => "".into(),
         };
 
-        metrics = metrics.finish();
+        // Written rather than printed, so piping into `head` fails quietly instead of panicking.
+        writeln!(io::stdout().lock(), "{output}").context("Failed to write the input")?;
 
-        print!("{answer1}{answer2}");
+        Ok(())
+    }
 
-        if metrics.enabled {
-            self.report_metrics(&metrics);
+    /// Prints the size of the input along with the statistics the problem reports about it.
+    pub fn inspect<T>(&self) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let (path, content) = self.read_content(&problem, &context)?;
+        let mut statistics = vec![
+            ("Lines", content.lines().count().to_string()),
+            ("Bytes", content.len().to_string()),
+        ];
+
+        let input = self.parse_content(&problem, &context, &path, &content)?;
+        statistics.extend(problem.inspect(&input));
+
+        if self.config.format == Format::Json {
+            let statistics = statistics
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>();
+            println!(
+                "{}",
+                json!({ "day": self.config.day, "statistics": statistics })
+            );
+
+            return Ok(());
+        }
+
+        let message = format!("Day {} input", self.config.day).bold();
+        println!("🎄 {message}");
+
+        let width = statistics
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+
+        for (name, value) in &statistics {
+            let label = format!("{name}:");
+            println!(
+                "{PADDING}{label:<width$} {}",
+                value.bold(),
+                width = width + 1
+            );
         }
 
         Ok(())
     }
 
-    fn read_input<T>(&self, problem: &T, metrics: &mut Metrics) -> Result<T::Input, Error>
+    /// Solves every profile's input under the input root, printing a matrix of whether each part
+    /// matches the answer recorded for that profile.
+    pub fn validate<T>(&self) -> Result<(), Error>
     where
-        T: Problem<'a>,
+        T: Problem,
     {
-        let path = &problem.path()?;
-        let content = fs::read_to_string(path)
-            .map_err(|error| Error::Parse(path.into(), error.to_string()))?;
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let day = self.config.day;
+        let message = format!("Day {day} validation").bold();
+        println!("🎄 {message}");
 
-        metrics
-            .track_parsing(|| problem.parse(&content, path))
-            .map_err(|error| match error {
-                error @ Error::Parse(..) => error,
-                error => Error::Parse(path.to_path_buf(), error.to_string()),
+        let root = self
+            .config
+            .input_root
+            .clone()
+            .unwrap_or_else(inputs::default_root);
+        let profiles = inputs::profiles(&root, day)?;
+        if profiles.is_empty() {
+            let root = root.display();
+            return Err(anyhow!("No profile under '{root}' has an input for day {day}").into());
+        }
+
+        let parts = match self.config.part {
+            Part::One => vec!["Part 1"],
+            Part::Two => vec!["Part 2"],
+            Part::Both => vec!["Part 1", "Part 2"],
+        };
+
+        let rows = profiles
+            .iter()
+            .map(|profile| {
+                let verdicts = self.validate_profile(&problem, &context, &root, profile);
+                (profile, verdicts)
             })
+            .collect::<Vec<_>>();
+
+        let width = profiles
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or_default()
+            .max("Profile".len());
+
+        let header = parts
+            .iter()
+            .map(|part| format!("{part:<column$}", column = Verdict::WIDTH))
+            .collect::<String>();
+        println!("{PADDING}{:<width$}  {}", "Profile", header.bold());
+
+        for (profile, verdicts) in &rows {
+            let cells = match verdicts {
+                Ok(verdicts) => verdicts.iter().map(Verdict::cell).collect::<String>(),
+                Err(_) => Verdict::error_cell().repeat(parts.len()),
+            };
+            println!("{PADDING}{profile:<width$}  {cells}");
+        }
+
+        let mut failed = 0;
+        let mut reasons = vec![];
+        for (profile, verdicts) in &rows {
+            match verdicts {
+                Ok(verdicts) => {
+                    for (part, verdict) in parts.iter().zip(verdicts) {
+                        if let Verdict::Fail(reason) = verdict {
+                            failed += 1;
+                            reasons.push(format!("{profile}, {part}:  {reason}"));
+                        }
+                    }
+                }
+                // Without a solved input every part fails, for the same reason.
+                Err(error) => {
+                    failed += parts.len();
+                    reasons.push(format!("{profile}:  {error}"));
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            return Ok(());
+        }
+
+        println!();
+        for reason in &reasons {
+            println!("{PADDING}{reason}");
+        }
+
+        let total = rows.len() * parts.len();
+        Err(anyhow!("{failed} of {total} answers failed").into())
+    }
+
+    /// Solves the selected parts of `profile`'s input, judging each against its recorded answer,
+    /// or fails when the input or its answers can't be read or prepared for solving.
+    fn validate_profile<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+        root: &Path,
+        profile: &str,
+    ) -> Result<Vec<Verdict>, Error>
+    where
+        T: Problem,
+    {
+        let day = self.config.day;
+        let answers = Answers::load(root, profile, day)?;
+        let path = inputs::path(root, Some(profile), day);
+        let input = self.parse_path(problem, context, &path)?;
+        self.check_assumptions(problem, &input)?;
+        let shared = problem.share(&input, context)?;
+
+        let mut verdicts = vec![];
+        if matches!(self.config.part, Part::One | Part::Both) {
+            let answer = problem.part1(&input, &shared, context);
+            verdicts.push(Verdict::judge(answer, answers.part1.as_ref()));
+        }
+
+        if matches!(self.config.part, Part::Two | Part::Both) {
+            let answer = problem.part2(&input, &shared, context);
+            verdicts.push(Verdict::judge(answer, answers.part2.as_ref()));
+        }
+
+        Ok(verdicts)
+    }
+
+    pub fn bench<T>(&self, sizes: &[usize], seed: u64) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let message = format!("Day {} benchmark", self.config.day).bold();
+        println!("🎄 {message}");
+
+        for size in sizes {
+            let content = problem.generate(&mut Rng::new(seed), *size)?;
+            let path = Path::new("<generated>");
+            let input = problem.parse(&content, path, &context)?;
+
+            println!("{PADDING}Size {}", size.to_string().bold());
+
+            // Both parts rely on the shared work, so each is charged for it.
+            let (shared, shared_time) = time(|| problem.share(&input, &context));
+            let shared = shared?;
+
+            if matches!(self.config.part, Part::One | Part::Both) {
+                let (answer, elapsed) = time(|| problem.part1(&input, &shared, &context));
+                let optimised = (answer, elapsed + shared_time);
+                let reference = time(|| problem.reference_part1(&input, &context));
+                Self::report_comparison("Part 1", optimised, reference);
+            }
+
+            if matches!(self.config.part, Part::Two | Part::Both) {
+                let (answer, elapsed) = time(|| problem.part2(&input, &shared, &context));
+                let optimised = (answer, elapsed + shared_time);
+                let reference = time(|| problem.reference_part2(&input, &context));
+                Self::report_comparison("Part 2", optimised, reference);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compares the optimised and reference answers on `cases` generated inputs of up to
+    /// `max_size`, stopping at the first case they disagree on.
+    pub fn diff_test<T>(
+        &self,
+        cases: usize,
+        max_size: usize,
+        seed: u64,
+        shrink_to: Option<&Path>,
+    ) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let message = format!("Day {} differential test", self.config.day).bold();
+        println!("🎄 {message}");
+
+        let mut seeds = Rng::new(seed);
+        for case in 1..=cases {
+            let case_seed = seeds.next_u64();
+            let size = seeds.range(1..=max_size.max(1) as u64) as usize;
+            let content = problem.generate(&mut Rng::new(case_seed), size)?;
+            let input = problem.parse(&content, Path::new("<generated>"), &context)?;
+            let shared = problem.share(&input, &context)?;
+
+            let mut mismatches = vec![];
+            if matches!(self.config.part, Part::One | Part::Both) {
+                let optimised = problem.part1(&input, &shared, &context);
+                let reference = problem.reference_part1(&input, &context);
+                if let Some(mismatch) = Self::compare(optimised, reference)? {
+                    mismatches.push(("Part 1", mismatch));
+                }
+            }
+
+            if matches!(self.config.part, Part::Two | Part::Both) {
+                let optimised = problem.part2(&input, &shared, &context);
+                let reference = problem.reference_part2(&input, &context);
+                if let Some(mismatch) = Self::compare(optimised, reference)? {
+                    mismatches.push(("Part 2", mismatch));
+                }
+            }
+
+            if mismatches.is_empty() {
+                continue;
+            }
+
+            println!("{PADDING}Case {case} (seed {case_seed}, size {size})");
+            for (label, mismatch) in mismatches {
+                println!("{PADDING}{PADDING}{label}:  {mismatch}");
+            }
+
+            println!(
+                "{PADDING}Reproduce with: cargo aoc gen --day {} --seed {case_seed} --size {size}{}",
+                self.config.day,
+                self.param_flags(),
+            );
+
+            if let Some(output) = shrink_to {
+                self.shrink_into(&problem, &context, &content, output)?;
+            }
+
+            return Err(anyhow!("Case {case} of {cases} didn't match the reference").into());
+        }
+
+        println!("{PADDING}All {} cases matched", cases.to_string().green());
+
+        Ok(())
+    }
+
+    /// Shrinks the input given with `--input`, or the day's own input, to a smaller one that
+    /// still fails in the same way, writing it to `output`.
+    pub fn shrink<T>(&self, output: &Path) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let message = format!("Day {} shrink", self.config.day).bold();
+        println!("🎄 {message}");
+
+        let (_, content) = self.read_content(&problem, &context)?;
+        self.shrink_into(&problem, &context, &content, output)
+    }
+
+    fn shrink_into<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+        content: &str,
+        output: &Path,
+    ) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        // Every candidate that panics would otherwise print its panic.
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+
+        let original = self.failures(problem, context, content);
+        let mut checks = 0;
+        let shrunk = match original.is_empty() {
+            true => None,
+            false => Some(shrink::shrink(content, |candidate| {
+                checks += 1;
+                let failures = self.failures(problem, context, candidate);
+                failures.iter().any(|failure| original.contains(failure))
+            })),
+        };
+
+        panic::set_hook(hook);
+
+        let Some(shrunk) = shrunk else {
+            let message = "The input neither panics nor disagrees with the reference";
+            return Err(anyhow!("{message}, so there's nothing to shrink").into());
+        };
+
+        fs::write(output, format!("{shrunk}\n")).with_context(|| {
+            format!("Failed to write the shrunk input to '{}'", output.display())
+        })?;
+
+        println!(
+            "{PADDING}Shrunk {} lines to {} in {checks} checks, still failing in {}",
+            content.lines().count(),
+            shrunk.lines().count().to_string().bold(),
+            original.join(", "),
+        );
+        println!(
+            "{PADDING}Run it with: cargo aoc run --day {} --input {}{}",
+            self.config.day,
+            output.display(),
+            self.param_flags(),
+        );
+
+        Ok(())
+    }
+
+    /// Feeds mutations of the day's input, and of a generated one, to parsing and the parts,
+    /// saving each input that panics somewhere new into `output`.
+    pub fn fuzz<T>(&self, iterations: usize, seed: u64, output: &Path) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let message = format!("Day {} fuzz", self.config.day).bold();
+        println!("🎄 {message}");
+
+        let real = self
+            .read_content(&problem, &context)
+            .map(|(_, content)| content);
+        let generated = problem.generate(&mut Rng::new(seed), 20);
+        let corpus = [real.ok(), generated.ok()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let Some(last) = corpus.len().checked_sub(1) else {
+            return Err(anyhow!("There's neither an input nor a generator to start from").into());
+        };
+
+        // Panics are recorded rather than printed, to report each place that panics once.
+        let panicked = Arc::new(Mutex::new(None));
+        let hook = panic::take_hook();
+        let recorder = Arc::clone(&panicked);
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(ToString::to_string).unwrap_or_default();
+            let message = info.payload_as_str().unwrap_or("unknown panic").to_string();
+            *recorder.lock().unwrap_or_else(|error| error.into_inner()) = Some((location, message));
+        }));
+
+        let mut rng = Rng::new(seed);
+        let mut locations = HashSet::new();
+        let mut saved = vec![];
+
+        for _ in 0..iterations {
+            let base = &corpus[rng.range(0..=last as u64) as usize];
+            let content = fuzz::mutate(&mut rng, base);
+            let Some(stage) = self.panicking_stage(&problem, &context, &content) else {
+                continue;
+            };
+
+            let recorded = panicked
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .take();
+            let (location, message) = recorded.unwrap_or_default();
+            if locations.insert(location.clone()) {
+                saved.push((stage, location, message, content));
+            }
+        }
+
+        panic::set_hook(hook);
+
+        if saved.is_empty() {
+            println!(
+                "{PADDING}{} iterations without a panic",
+                iterations.to_string().green()
+            );
+            return Ok(());
+        }
+
+        fs::create_dir_all(output)
+            .with_context(|| format!("Failed to create '{}'", output.display()))?;
+
+        for (index, (stage, location, message, content)) in saved.iter().enumerate() {
+            let path = output.join(format!("day{}-{}.txt", self.config.day, index + 1));
+            fs::write(&path, content)
+                .with_context(|| format!("Failed to save the input to '{}'", path.display()))?;
+
+            println!("{PADDING}{stage} panicked at {location}: {}", message.red());
+            println!("{PADDING}{PADDING}Saved to {}", path.display());
+        }
+
+        let places = match saved.len() {
+            1 => "1 place".to_string(),
+            count => format!("{count} places"),
+        };
+        Err(anyhow!("Found {places} that panic in {iterations} iterations").into())
+    }
+
+    /// The first stage of solving `content` that panics, if any.
+    fn panicking_stage<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+        content: &str,
+    ) -> Option<&'static str>
+    where
+        T: Problem,
+    {
+        let path = Path::new("<fuzzed>");
+        let input =
+            match panic::catch_unwind(AssertUnwindSafe(|| problem.parse(content, path, context))) {
+                Ok(Ok(input)) => input,
+                Ok(Err(_)) => return None,
+                Err(_) => return Some("Parsing"),
+            };
+
+        // The runner refuses inputs breaking an assumption, so they can't panic while solving.
+        if self.check_assumptions(problem, &input).is_err() {
+            return None;
+        }
+
+        let shared = match panic::catch_unwind(AssertUnwindSafe(|| problem.share(&input, context)))
+        {
+            Ok(Ok(shared)) => shared,
+            Ok(Err(_)) => return None,
+            Err(_) => return Some("Shared"),
+        };
+
+        if matches!(self.config.part, Part::One | Part::Both) {
+            let part1 =
+                panic::catch_unwind(AssertUnwindSafe(|| problem.part1(&input, &shared, context)));
+            if part1.is_err() {
+                return Some("Part 1");
+            }
+        }
+
+        if matches!(self.config.part, Part::Two | Part::Both) {
+            let part2 =
+                panic::catch_unwind(AssertUnwindSafe(|| problem.part2(&input, &shared, context)));
+            if part2.is_err() {
+                return Some("Part 2");
+            }
+        }
+
+        None
+    }
+
+    /// The stages that go wrong on `content`, by panicking or, for the parts, disagreeing with
+    /// the reference. Input that's rejected with an error, or that breaks an assumption, has
+    /// nothing wrong with it to find.
+    fn failures<T>(&self, problem: &T, context: &SolveContext, content: &str) -> Vec<&'static str>
+    where
+        T: Problem,
+    {
+        let path = Path::new("<shrinking>");
+        let input =
+            match panic::catch_unwind(AssertUnwindSafe(|| problem.parse(content, path, context))) {
+                Ok(Ok(input)) => input,
+                Ok(Err(_)) => return vec![],
+                Err(_) => return vec!["Parsing"],
+            };
+
+        if self.check_assumptions(problem, &input).is_err() {
+            return vec![];
+        }
+
+        let shared = match panic::catch_unwind(AssertUnwindSafe(|| problem.share(&input, context)))
+        {
+            Ok(Ok(shared)) => shared,
+            Ok(Err(_)) => return vec![],
+            Err(_) => return vec!["Shared"],
+        };
+
+        let mut failures = vec![];
+        if matches!(self.config.part, Part::One | Part::Both) {
+            let optimised = || problem.part1(&input, &shared, context);
+            let reference = || problem.reference_part1(&input, context);
+            if Self::disagrees(optimised, reference) {
+                failures.push("Part 1");
+            }
+        }
+
+        if matches!(self.config.part, Part::Two | Part::Both) {
+            let optimised = || problem.part2(&input, &shared, context);
+            let reference = || problem.reference_part2(&input, context);
+            if Self::disagrees(optimised, reference) {
+                failures.push("Part 2");
+            }
+        }
+
+        failures
+    }
+
+    /// Whether either solution panics, or they give different answers.
+    fn disagrees<R, TOptimised, TReference>(optimised: TOptimised, reference: TReference) -> bool
+    where
+        R: Display,
+        TOptimised: FnOnce() -> Result<R, Error>,
+        TReference: FnOnce() -> Result<R, Error>,
+    {
+        let optimised = panic::catch_unwind(AssertUnwindSafe(optimised));
+        let reference = panic::catch_unwind(AssertUnwindSafe(reference));
+
+        match (optimised, reference) {
+            (Ok(optimised), Ok(reference)) => {
+                matches!(Self::compare(optimised, reference), Ok(Some(_)))
+            }
+            _ => true,
+        }
+    }
+
+    /// The `--param` flags given, to repeat in suggested commands.
+    fn param_flags(&self) -> String {
+        self.config
+            .params
+            .iter()
+            .map(|(name, value)| format!(" --param {name}={value}"))
+            .collect()
+    }
+
+    /// Describes how the answers differ, if they do. Inputs both solutions reject count as
+    /// agreeing, but a missing reference fails the whole test.
+    fn compare<R>(
+        optimised: Result<R, Error>,
+        reference: Result<R, Error>,
+    ) -> Result<Option<String>, Error>
+    where
+        R: Display,
+    {
+        match (&optimised, &reference) {
+            (_, Err(Error::Unimplemented)) => Err(Error::Unimplemented),
+            (Ok(a), Ok(b)) if a.to_string() == b.to_string() => Ok(None),
+            (Err(_), Err(_)) => Ok(None),
+            _ => Ok(Some(format!(
+                "{} != {} reference",
+                optimised.format(),
+                reference.format()
+            ))),
+        }
+    }
+
+    /// Generates an input of `size` from `seed`, checking it parses before writing it to `output`
+    /// or stdout.
+    pub fn generate<T>(&self, size: usize, seed: u64, output: Option<&Path>) -> Result<(), Error>
+    where
+        T: Problem,
+    {
+        let (problem, context) = self.init::<T>(Arc::new(Metrics::start(false)))?;
+        let content = problem.generate(&mut Rng::new(seed), size)?;
+        problem.parse(&content, Path::new("<generated>"), &context)?;
+
+        match output {
+            Some(path) => fs::write(path, content + "\n")
+                .with_context(|| format!("Failed to write the input to '{}'", path.display()))?,
+            None => println!("{content}"),
+        }
+
+        Ok(())
+    }
+
+    fn report_comparison<R>(
+        label: &str,
+        (optimised, optimised_time): (Result<R, Error>, Duration),
+        (reference, reference_time): (Result<R, Error>, Duration),
+    ) where
+        R: Display,
+    {
+        let answers = match (&optimised, &reference) {
+            (Ok(a), Ok(b)) if a.to_string() == b.to_string() => optimised.format(),
+            (Ok(_), Ok(_)) => format!("{} != {}", optimised.format(), reference.format()).red(),
+            (Err(_), _) => optimised.format(),
+            (_, Err(_)) => reference.format(),
+        };
+
+        let speedup = reference_time.as_secs_f64() / optimised_time.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "{PADDING}{PADDING}{label}:  {answers}, {} vs {} reference ({})",
+            optimised_time.human(Truncate::Micro).to_string().yellow(),
+            reference_time.human(Truncate::Micro).to_string().yellow(),
+            format!("{speedup:.1}x").bold(),
+        );
+    }
+
+    fn read_input<T>(&self, problem: &T, context: &SolveContext) -> Result<T::Input, Error>
+    where
+        T: Problem,
+    {
+        let path = self.input_path(problem)?;
+        self.parse_path(problem, context, &path)
+    }
+
+    /// Parses the input at `path`, letting the problem read the file itself when it can and
+    /// otherwise reading all of it first.
+    fn parse_path<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+        path: &Path,
+    ) -> Result<T::Input, Error>
+    where
+        T: Problem,
+    {
+        match problem.parse_file(path, context) {
+            Some(input) => input.map_err(|error| parse_error(path, error)),
+            None => {
+                let content = Self::read_file(path, context)?;
+                self.parse_content(problem, context, path, &content)
+            }
+        }
+    }
+
+    /// Parses `content` read from `path`, reporting any error against the path.
+    fn parse_content<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+        path: &Path,
+        content: &str,
+    ) -> Result<T::Input, Error>
+    where
+        T: Problem,
+    {
+        context
+            .span("Parsing", || problem.parse(content, path, context))
+            .map_err(|error| parse_error(path, error))
+    }
+
+    /// The input given with `--input`, or else the day's input under the configured root and
+    /// profile. Without either, the problem's own input beside its code is read when run from the
+    /// repository, and otherwise the day's input under the default root.
+    fn input_path<T>(&self, problem: &T) -> Result<PathBuf, Error>
+    where
+        T: Problem,
+    {
+        if let Some(path) = &self.config.input {
+            return Ok(path.clone());
+        }
+
+        let (root, profile) = (&self.config.input_root, self.config.profile.as_deref());
+        if root.is_none() && profile.is_none() {
+            let path = problem.path()?;
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+
+        let root = root.clone().unwrap_or_else(inputs::default_root);
+        Ok(inputs::path(&root, profile, self.config.day))
+    }
+
+    /// Reads the input from wherever `input_path` finds it.
+    fn read_content<T>(
+        &self,
+        problem: &T,
+        context: &SolveContext,
+    ) -> Result<(PathBuf, String), Error>
+    where
+        T: Problem,
+    {
+        let path = self.input_path(problem)?;
+        let content = Self::read_file(&path, context)?;
+
+        Ok((path, content))
+    }
+
+    fn read_file(path: &Path, context: &SolveContext) -> Result<String, Error> {
+        context
+            .span(READING, || fs::read_to_string(path))
+            .map_err(|error| Error::Parse(path.to_path_buf(), error.to_string()))
+    }
+
+    fn print_json<A, B>(
+        &self,
+        answer1: &Option<Result<A, Error>>,
+        answer2: &Option<Result<B, Error>>,
+        metrics: &Metrics,
+    ) where
+        A: Display,
+        B: Display,
+    {
+        fn answer<R: Display>(answer: &Result<R, Error>) -> Value {
+            match answer {
+                Ok(answer) => json!({ "answer": answer.to_string() }),
+                Err(error) => json!({ "error": error.to_string() }),
+            }
+        }
+
+        let mut output = json!({ "day": self.config.day });
+        if let Some(answer1) = answer1 {
+            output["part1"] = answer(answer1);
+        }
+
+        if let Some(answer2) = answer2 {
+            output["part2"] = answer(answer2);
+        }
+
+        if metrics.enabled {
+            let spans = metrics
+                .spans()
+                .into_iter()
+                .map(|(depth, span)| {
+                    json!({
+                        "name": span.name,
+                        "depth": depth,
+                        "count": span.count,
+                        "total_us": span.total.as_micros() as u64,
+                        "user_us": span.usage.user.as_micros() as u64,
+                        "system_us": span.usage.system.as_micros() as u64,
+                        "blocks_read": span.usage.blocks_read,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            output["metrics"] = json!({
+                "spans": spans,
+                "total_us": metrics.total().as_micros() as u64,
+                "wall_us": metrics.wall().as_micros() as u64,
+            });
+        }
+
+        println!("{output}");
+    }
+
+    fn write_timeline(metrics: &Metrics, path: &Path) -> Result<(), Error> {
+        let timeline = serde_json::to_string(&metrics.timeline())
+            .context("Failed to serialise the timeline")?;
+        fs::write(path, timeline)
+            .with_context(|| format!("Failed to write the timeline to '{}'", path.display()))?;
+
+        Ok(())
     }
 
     fn report_metrics(&self, metrics: &Metrics) {
         println!();
         println!("{}", "🎁 Metrics".bold());
 
-        if let Some(parsing) = metrics.parsing {
+        let spans = metrics.spans();
+        let width = spans
+            .iter()
+            .map(|(depth, span)| depth * PADDING.len() + span.name.len())
+            .max()
+            .unwrap_or_default()
+            .max("Total".len());
+
+        for (depth, span) in &spans {
+            let indent = PADDING.repeat(*depth);
+            let label = format!("{indent}{}:", span.name);
+            let calls = match span.count {
+                1 => String::new(),
+                count => format!(" over {count} calls"),
+            };
+
+            let total = span.total.human(Truncate::Micro).to_string().yellow();
+            let cpu = format!(
+                " ({} user, {} system)",
+                human(span.usage.user),
+                human(span.usage.system),
+            );
             println!(
-                "{PADDING}Parsing: {}",
-                parsing.human(Truncate::Micro).to_string().yellow()
+                "{PADDING}{label:<width$} {total}{calls}{}",
+                cpu.dimmed(),
+                width = width + 1
             );
         }
 
-        if let Some(part1) = metrics.part1 {
+        let total = metrics.total().human(Truncate::Micro).to_string().yellow();
+        println!("{PADDING}{:<width$} {total}", "Total:", width = width + 1);
+
+        let wall = metrics.wall().human(Truncate::Micro).to_string().yellow();
+        println!("{PADDING}{:<width$} {wall}", "Wall:", width = width + 1);
+
+        // Reading from storage rather than the page cache shows up as blocks read.
+        if let Some((_, reading)) = spans
+            .iter()
+            .find(|(depth, span)| *depth == 0 && span.name == READING)
+        {
+            let cache = match reading.usage.blocks_read {
+                0 => "warm cache".to_string(),
+                blocks => format!("cold cache, {blocks} blocks read"),
+            };
+            let io = reading.total.human(Truncate::Micro).to_string().yellow();
             println!(
-                "{PADDING}Part 1:  {}",
-                part1.human(Truncate::Micro).to_string().yellow()
+                "{PADDING}{:<width$} {io} ({cache})",
+                "I/O:",
+                width = width + 1
             );
         }
+    }
+}
 
-        if let Some(part2) = metrics.part2 {
-            println!(
-                "{PADDING}Part 2:  {}",
-                part2.human(Truncate::Micro).to_string().yellow()
-            );
+/// How a profile's answer to a part compares with the one recorded for it.
+enum Verdict {
+    Pass,
+    Fail(String),
+
+    /// There's no recorded answer to compare with, or no solution to the part yet.
+    Unknown,
+}
+
+impl Verdict {
+    fn judge<R>(answer: Result<R, Error>, recorded: Option<&Answer>) -> Self
+    where
+        R: Display,
+    {
+        match (answer, recorded) {
+            (Err(Error::Unimplemented), _) | (Ok(_), None) => Self::Unknown,
+            (Err(error), _) => Self::Fail(error.to_string()),
+            (Ok(answer), Some(recorded)) if answer.to_string() == recorded.to_string() => {
+                Self::Pass
+            }
+            (Ok(answer), Some(recorded)) => Self::Fail(format!(
+                "{} != {recorded} recorded",
+                answer.to_string().red()
+            )),
         }
+    }
 
-        println!(
-            "{PADDING}Total:   {}",
-            metrics.total.human(Truncate::Micro).to_string().yellow()
-        );
+    /// How wide each part's column of the matrix is.
+    const WIDTH: usize = 9;
+
+    /// The verdict padded to a column of the matrix.
+    fn cell(&self) -> String {
+        let width = Self::WIDTH;
+        match self {
+            Self::Pass => format!("{:<width$}", "pass").green().to_string(),
+            Self::Fail(_) => format!("{:<width$}", "fail").red().to_string(),
+            Self::Unknown => format!("{:<width$}", "unknown").yellow().to_string(),
+        }
+    }
+
+    /// The cell for a part that couldn't be solved because its input couldn't be prepared.
+    fn error_cell() -> String {
+        let width = Self::WIDTH;
+        format!("{:<width$}", "error").red().to_string()
+    }
+}
+
+/// Formats `duration` to the microsecond, spelling out durations too short to register.
+fn human(duration: Duration) -> String {
+    match duration.as_micros() {
+        0 => "0µs".to_string(),
+        _ => duration.human(Truncate::Micro).to_string(),
+    }
+}
+
+fn time<TFn, R>(f: TFn) -> (R, Duration)
+where
+    TFn: FnOnce() -> R,
+{
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Reports a failure to parse the input as an error against its `path`.
+fn parse_error(path: &Path, error: Error) -> Error {
+    match error {
+        error @ Error::Parse(..) => error,
+        error => Error::Parse(path.to_path_buf(), error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Solves part 1 and fails part 2, whatever the input.
+    struct Failing;
+
+    impl Problem for Failing {
+        type Input = ();
+        type Answer1 = u64;
+        type Answer2 = u64;
+        type Shared = ();
+
+        fn init(_context: &SolveContext) -> Result<Self, Error> {
+            Ok(Self)
+        }
+
+        fn parse(
+            &self,
+            _content: &str,
+            _path: &Path,
+            _context: &SolveContext,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn part1(&self, _input: &(), _shared: &(), _context: &SolveContext) -> Result<u64, Error> {
+            Ok(1)
+        }
+
+        fn part2(&self, _input: &(), _shared: &(), _context: &SolveContext) -> Result<u64, Error> {
+            Err(anyhow!("Part 2 can't be solved").into())
+        }
+    }
+
+    fn config(part: Part) -> RunConfig {
+        RunConfig {
+            day: 1,
+            part,
+            verbose: false,
+            metrics: false,
+            input: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")),
+            input_root: None,
+            profile: None,
+            reference: false,
+            params: vec![],
+            timeout: None,
+            trace_timeline: None,
+            format: Format::Text,
+        }
+    }
+
+    #[test]
+    fn succeeds_when_every_part_is_solved() {
+        let config = config(Part::One);
+
+        assert!(Runner::new(&config).run::<Failing>().is_ok());
+    }
+
+    #[test]
+    fn fails_when_a_part_fails() {
+        for part in [Part::Two, Part::Both] {
+            let config = config(part.clone());
+            let result = Runner::new(&config).run::<Failing>();
+
+            assert!(result.is_err(), "{part:?}");
+        }
     }
 }
@@ -0,0 +1,144 @@
+/// Shrinks `content` while `fails` still holds, first removing whole lines, then comma separated
+/// items within each line, then single characters, until none of them can be removed.
+///
+/// Everything works on the text of the input, so it suits any problem: rotations, ranges and
+/// banks are lines, the second day's ranges are items, and grid cells and digits are characters.
+pub fn shrink<TFn>(content: &str, mut fails: TFn) -> String
+where
+    TFn: FnMut(&str) -> bool,
+{
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+
+    loop {
+        let before = lines.clone();
+
+        lines = ddmin(lines, &mut |lines| fails(&lines.join("\n")));
+
+        for index in 0..lines.len() {
+            for separator in [Some(','), None] {
+                let parts = split(&lines[index], separator);
+                let parts = ddmin(parts, &mut |parts| {
+                    let mut candidate = lines.clone();
+                    candidate[index] = join(parts, separator);
+                    fails(&candidate.join("\n"))
+                });
+
+                lines[index] = join(&parts, separator);
+            }
+        }
+
+        if lines == before {
+            return lines.join("\n");
+        }
+    }
+}
+
+/// Removes as many of `items` as it can while `fails` still holds, following Zeller's ddmin:
+/// try dropping each of `n` chunks, and split into finer chunks whenever none can be dropped.
+fn ddmin<TFn>(mut items: Vec<String>, fails: &mut TFn) -> Vec<String>
+where
+    TFn: FnMut(&[String]) -> bool,
+{
+    let mut chunks = 2;
+
+    while items.len() >= 2 {
+        let size = items.len().div_ceil(chunks);
+        let complement = (0..items.len()).step_by(size).find_map(|start| {
+            let end = (start + size).min(items.len());
+            let complement = [&items[..start], &items[end..]].concat();
+            fails(&complement).then_some(complement)
+        });
+
+        match complement {
+            Some(complement) => {
+                items = complement;
+                chunks = (chunks - 1).max(2);
+            }
+            None if chunks >= items.len() => break,
+            None => chunks = (chunks * 2).min(items.len()),
+        }
+    }
+
+    if items.len() == 1 && fails(&[]) {
+        items.clear();
+    }
+
+    items
+}
+
+/// Splits a line into items around `separator`, or into characters without one.
+fn split(line: &str, separator: Option<char>) -> Vec<String> {
+    match separator {
+        Some(separator) => line.split(separator).map(str::to_string).collect(),
+        None => line.chars().map(String::from).collect(),
+    }
+}
+
+fn join(parts: &[String], separator: Option<char>) -> String {
+    match separator {
+        Some(separator) => parts.join(&separator.to_string()),
+        None => parts.concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(value: &str) -> Vec<String> {
+        value.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn keeps_the_one_item_that_fails() {
+        let shrunk = ddmin(items("abcdefgh"), &mut |items| {
+            items.contains(&"c".to_string())
+        });
+
+        assert_eq!(shrunk, items("c"));
+    }
+
+    #[test]
+    fn keeps_every_item_needed_to_fail() {
+        let fails = |items: &[String]| {
+            ["b", "e", "h"]
+                .iter()
+                .all(|item| items.contains(&item.to_string()))
+        };
+        let shrunk = ddmin(items("abcdefghij"), &mut |items| fails(items));
+
+        assert_eq!(shrunk, items("beh"));
+    }
+
+    #[test]
+    fn removes_everything_when_nothing_is_needed_to_fail() {
+        assert!(ddmin(items("abcd"), &mut |_| true).is_empty());
+        assert!(ddmin(items("a"), &mut |_| true).is_empty());
+    }
+
+    #[test]
+    fn keeps_everything_when_every_item_is_needed() {
+        let shrunk = ddmin(items("abcd"), &mut |items| items.len() == 4);
+
+        assert_eq!(shrunk, items("abcd"));
+    }
+
+    #[test]
+    fn shrinks_lines_then_items_then_characters() {
+        let content = "L68\nR48\n11-22,95-115,998-1012\nL5";
+        let shrunk = shrink(content, |candidate| candidate.contains("9-1"));
+
+        assert_eq!(shrunk, "9-1");
+    }
+
+    #[test]
+    fn shrinks_to_an_input_that_still_fails() {
+        let content = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.";
+        let fails =
+            |candidate: &str| candidate.matches('@').count() >= 3 && candidate.lines().count() >= 2;
+        let shrunk = shrink(content, fails);
+
+        assert!(fails(&shrunk));
+        assert_eq!(shrunk.len(), "@@\n@".len());
+    }
+}